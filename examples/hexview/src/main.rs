@@ -1,8 +1,12 @@
 use iced::{
-    pick_list, scrollable, slider, Align, Checkbox, Column, Container, Element, Font, Length,
-    PickList, Radio, Row, Sandbox, Scrollable, Settings, Slider, Text,
+    pick_list, slider, Align, Checkbox, Column, Container, Element, Font, Length, PickList, Radio,
+    Row, Sandbox, Settings, Slider, Text,
 };
-use iced_widgets::{native::hexview, style::hexview as hexview_style};
+use iced_widgets::{
+    native::{hexview::{self, encoding::Builtin as Encoding}, inspector},
+    style::{hexview as hexview_style, inspector as inspector_style},
+};
+use std::ops::Range;
 
 macro_rules! load_data {
     ($p:expr) => {
@@ -35,14 +39,20 @@ const TGA_IMAGE: &[u8] = load_data!("black_square.tga");
 const PNG_IMAGE: &[u8] = load_data!("black_square.png");
 const SAMPLE_OPTIONS: &[&'static str] = &["Lorem Ipsum", "TGA Image", "PNG Image"];
 const FONT_OPTIONS: &[&'static str] = &["Default", "Noto Sans", "Hack"];
+const ENCODING_OPTIONS: &[Encoding] = &Encoding::ALL;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ColumnCount(u8),
+    FitColumns(bool),
     ThemeSelected(Theme),
     ContentSelected(&'static str),
     FontSelected(&'static str),
+    EncodingSelected(Encoding),
     HighlightNonPrintable(bool),
+    CursorMoved(usize),
+    SelectionChanged(Option<Range<usize>>),
+    ValueWritten(usize, Vec<u8>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,13 +66,18 @@ pub struct App {
     content_name: &'static str,
     font_name: &'static str,
     hexview_fonts: (Font, Font),
+    encoding: Encoding,
     highlight_np: bool,
     hexview_columns: u8,
+    fit_columns: bool,
     hexview: hexview::State,
+    inspector: inspector::State,
+    cursor: usize,
+    selection: Option<Range<usize>>,
     column_slider: slider::State,
     content_list: pick_list::State<&'static str>,
-    scrollable: scrollable::State,
     font_list: pick_list::State<&'static str>,
+    encoding_list: pick_list::State<Encoding>,
 }
 
 pub struct HexviewTheme {
@@ -79,20 +94,25 @@ impl Sandbox for App {
 
     fn new() -> App {
         let mut hexview = hexview::State::new();
-        hexview.set_bytes(LOREM_IPSUM);
+        hexview.set_source(LOREM_IPSUM);
 
         App {
             hexview,
+            inspector: inspector::State::new(),
+            cursor: 0,
+            selection: None,
             hexview_theme: Theme::Light,
             content_name: "Lorem Ipsum",
             font_name: "Default",
             hexview_fonts: (Font::Default, Font::Default),
+            encoding: Encoding::Ascii,
             highlight_np: true,
             hexview_columns: 16,
+            fit_columns: false,
             column_slider: slider::State::new(),
             content_list: pick_list::State::default(),
-            scrollable: scrollable::State::new(),
             font_list: pick_list::State::default(),
+            encoding_list: pick_list::State::default(),
         }
     }
 
@@ -103,16 +123,19 @@ impl Sandbox for App {
     fn update(&mut self, event: Message) {
         match event {
             Message::ColumnCount(n) => self.hexview_columns = n,
+            Message::FitColumns(fit) => self.fit_columns = fit,
             Message::ThemeSelected(t) => self.hexview_theme = t,
             Message::ContentSelected(name) => {
                 match name {
-                    "Lorem Ipsum" => self.hexview.set_bytes(LOREM_IPSUM),
-                    "TGA Image" => self.hexview.set_bytes(TGA_IMAGE),
-                    "PNG Image" => self.hexview.set_bytes(PNG_IMAGE),
+                    "Lorem Ipsum" => self.hexview.set_source(LOREM_IPSUM),
+                    "TGA Image" => self.hexview.set_source(TGA_IMAGE),
+                    "PNG Image" => self.hexview.set_source(PNG_IMAGE),
                     _ => (),
                 }
 
                 self.content_name = name;
+                self.cursor = 0;
+                self.selection = None;
             }
             Message::FontSelected(name) => {
                 match name {
@@ -124,16 +147,22 @@ impl Sandbox for App {
 
                 self.font_name = name;
             }
+            Message::EncodingSelected(encoding) => self.encoding = encoding,
             Message::HighlightNonPrintable(b) => {
                 self.highlight_np = b;
             }
+            Message::CursorMoved(cursor) => self.cursor = cursor,
+            Message::SelectionChanged(selection) => self.selection = selection,
+            Message::ValueWritten(offset, bytes) => {
+                self.hexview.write(offset, &bytes);
+            }
         }
     }
 
     fn view(&mut self) -> Element<Message> {
         let column_slider = Slider::new(
             &mut self.column_slider,
-            1.0..=32.0,
+            1.0..=hexview::MAX_COLUMNS as f32,
             self.hexview_columns as f32,
             |n| Message::ColumnCount(n.floor() as u8),
         )
@@ -166,6 +195,19 @@ impl Sandbox for App {
             Message::FontSelected,
         );
 
+        let encoding_list = PickList::new(
+            &mut self.encoding_list,
+            ENCODING_OPTIONS,
+            Some(self.encoding),
+            Message::EncodingSelected,
+        );
+
+        let fit_ckb = Checkbox::new(
+            self.fit_columns,
+            "Fit",
+            Message::FitColumns,
+        );
+
         let highlight_ckb = Checkbox::new(
             self.highlight_np,
             "Highlight non-printable",
@@ -176,6 +218,7 @@ impl Sandbox for App {
             Text::new("Column Count:").into(),
             column_slider.into(),
             Text::new(format!("{}", self.hexview_columns)).into(),
+            fit_ckb.into(),
             Text::new("Theme:").into(),
             light_radio.into(),
             dark_radio.into(),
@@ -183,22 +226,56 @@ impl Sandbox for App {
             font_list.into(),
             Text::new("Content:").into(),
             content_list.into(),
+            Text::new("Encoding:").into(),
+            encoding_list.into(),
             highlight_ckb.into(),
         ])
         .align_items(Align::Center)
         .spacing(12)
         .padding(8);
 
+        self.inspector.inspect(&self.hexview);
+
+        let inspector_theme: Box<dyn inspector_style::StyleSheet> = match self.hexview_theme {
+            Theme::Light => Box::new(inspector_style::Light),
+            Theme::Dark => Box::new(inspector_style::Dark),
+        };
+        let inspector = inspector::Inspector::new(&mut self.inspector)
+            .style(inspector_theme)
+            .data_font(self.hexview_fonts.0)
+            .header_font(self.hexview_fonts.1)
+            .width(Length::Units(480))
+            .on_write(Message::ValueWritten);
+
         let hexview_theme = modify_theme(self.hexview_theme, self.highlight_np);
+        let columns = if self.fit_columns {
+            hexview::Columns::Fit { multiple_of: hexview::ColumnMultiple::PowerOfTwo }
+        } else {
+            hexview::Columns::Fixed(self.hexview_columns)
+        };
         let hexview = hexview::Hexview::new(&mut self.hexview)
             .style(hexview_theme)
             .data_font(self.hexview_fonts.0)
             .header_font(self.hexview_fonts.1)
-            .column_count(self.hexview_columns);
+            .columns(columns)
+            .encoding(self.encoding)
+            .read_only(false)
+            .on_cursor_move(Message::CursorMoved)
+            .on_selection(Message::SelectionChanged);
 
-        let scrollable = Scrollable::new(&mut self.scrollable).push(hexview);
+        let status = match &self.selection {
+            Some(selection) => format!(
+                "Selection: {:08X}-{:08X} ({} bytes)",
+                selection.start,
+                selection.end,
+                selection.end - selection.start,
+            ),
+            None => format!("Offset: {:08X}", self.cursor),
+        };
+        let status_bar = Row::with_children(vec![Text::new(status).into()]).padding(8);
 
-        let column = Column::with_children(vec![row.into(), scrollable.into()]);
+        let content = Row::with_children(vec![hexview.into(), inspector.into()]);
+        let column = Column::with_children(vec![row.into(), content.into(), status_bar.into()]);
 
         Container::new(column)
             .width(Length::Fill)
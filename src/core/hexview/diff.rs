@@ -0,0 +1,154 @@
+//! Comparison of the data of two [`Hexview`]s.
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+use super::ByteSource;
+use std::ops::Range;
+
+/// How many bytes are compared at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Finds the ranges of bytes that differ between `a` and `b`.
+///
+/// The ranges are sorted and never touch each other. When one source is
+/// longer, the bytes past the end of the other one are a difference too.
+pub fn find_differences(a: &dyn ByteSource, b: &dyn ByteSource) -> Vec<Range<usize>> {
+    let mut differences = Vec::new();
+
+    compare(a, b, 0..a.len().max(b.len()), &mut differences);
+    differences
+}
+
+/// Updates the `differences` found between `a` and `b` after the bytes in
+/// `edited` changed in either of them, comparing only those.
+///
+/// When the length of a source changed, every byte after the edit moved, so
+/// `edited` should go up to `usize::MAX` to compare everything past its
+/// start.
+pub fn update_differences(
+    differences: &[Range<usize>],
+    a: &dyn ByteSource,
+    b: &dyn ByteSource,
+    edited: Range<usize>,
+) -> Vec<Range<usize>> {
+    let longest = a.len().max(b.len());
+    let end = edited.end.min(longest);
+    let start = edited.start.min(end);
+    let mut updated = Vec::with_capacity(differences.len());
+
+    for range in differences.iter().take_while(|range| range.start < start) {
+        push(&mut updated, range.start..range.end.min(start));
+    }
+
+    compare(a, b, start..end, &mut updated);
+
+    for range in differences.iter().filter(|range| range.end > end) {
+        push(&mut updated, range.start.max(end)..range.end.min(longest));
+    }
+
+    updated
+}
+
+/// Compares the bytes of `a` and `b` in `range`, pushing the ones that
+/// differ to `differences`.
+fn compare(
+    a: &dyn ByteSource,
+    b: &dyn ByteSource,
+    range: Range<usize>,
+    differences: &mut Vec<Range<usize>>,
+) {
+    let common = a.len().min(b.len());
+    let mut buffers = (vec![0; CHUNK_SIZE], vec![0; CHUNK_SIZE]);
+    let mut offset = range.start;
+
+    while offset < common.min(range.end) {
+        let size = CHUNK_SIZE.min(common.min(range.end) - offset);
+        let chunk_a = &mut buffers.0[..size];
+        let chunk_b = &mut buffers.1[..size];
+
+        a.read(offset, chunk_a);
+        b.read(offset, chunk_b);
+
+        if chunk_a != chunk_b {
+            let mut start = None;
+
+            for (i, (byte_a, byte_b)) in chunk_a.iter().zip(chunk_b.iter()).enumerate() {
+                match (byte_a != byte_b, start) {
+                    (true, None) => start = Some(i),
+                    (false, Some(first)) => {
+                        push(differences, offset + first..offset + i);
+                        start = None;
+                    }
+                    _ => (),
+                }
+            }
+
+            if let Some(first) = start {
+                push(differences, offset + first..offset + size);
+            }
+        }
+
+        offset += size;
+    }
+
+    // Only one of the sources has these bytes
+    push(differences, range.start.max(common)..range.end.min(a.len().max(b.len())));
+}
+
+/// Pushes `range` to the sorted `differences`, merging it with the last one
+/// if they touch.
+fn push(differences: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if range.start >= range.end {
+        return;
+    }
+
+    match differences.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => differences.push(range),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_differences, update_differences, CHUNK_SIZE};
+
+    #[test]
+    fn test_differences() {
+        let a = b"same, different, same".to_vec();
+        let b = b"same, DIFFERent, same!!".to_vec();
+
+        assert_eq!(find_differences(&a, &b), vec![6..12, 21..23]);
+        assert_eq!(find_differences(&b, &a), vec![6..12, 21..23]);
+        assert!(find_differences(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_across_chunks() {
+        let a = vec![0; CHUNK_SIZE * 2];
+        let mut b = a.clone();
+
+        for byte in &mut b[CHUNK_SIZE - 2..CHUNK_SIZE + 3] {
+            *byte = 1;
+        }
+
+        assert_eq!(find_differences(&a, &b), vec![CHUNK_SIZE - 2..CHUNK_SIZE + 3]);
+    }
+
+    #[test]
+    fn test_update() {
+        let a = b"same, different, same".to_vec();
+        let mut b = b"same, DIFFERent, same!!".to_vec();
+        let differences = find_differences(&a, &b);
+
+        b[7..9].copy_from_slice(b"if");
+        b[0] = b'S';
+        let updated = update_differences(&differences, &a, &b, 7..9);
+        let updated = update_differences(&updated, &a, &b, 0..1);
+        assert_eq!(updated, vec![0..1, 6..7, 9..12, 21..23]);
+        assert_eq!(updated, find_differences(&a, &b));
+
+        b.truncate(10);
+        let updated = update_differences(&updated, &a, &b, 10..usize::MAX);
+        assert_eq!(updated, find_differences(&a, &b));
+    }
+}
@@ -0,0 +1,19 @@
+//! Changes made to the data of an [`Hexview`].
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+/// A change made to the data of an [`Hexview`].
+///
+/// Every change is a replacement of `old` by `new` at `offset`. Both have the
+/// same length when bytes are overwritten.
+///
+/// [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// Where the change starts.
+    pub offset: usize,
+    /// The bytes that were there before the change.
+    pub old: Vec<u8>,
+    /// The bytes that replaced them.
+    pub new: Vec<u8>,
+}
@@ -0,0 +1,380 @@
+//! Text encodings for the character pane of an [`Hexview`].
+//!
+//! An [`Encoding`] decodes a character from the start of some bytes. The
+//! character is shown on its first byte, while the rest of its bytes are
+//! continuations of it.
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+//! [`Encoding`]: trait.Encoding.html
+
+use super::{ByteSource, Endianness};
+use std::{fmt, ops::Range};
+
+/// A way to turn bytes into characters, and characters back into bytes.
+pub trait Encoding {
+    /// Returns the name of the [`Encoding`], like `UTF-8`.
+    ///
+    /// [`Encoding`]: trait.Encoding.html
+    fn name(&self) -> &str;
+
+    /// Returns the most bytes a character takes.
+    fn max_len(&self) -> usize {
+        1
+    }
+
+    /// Returns the amount of bytes characters are aligned to, counting from
+    /// the start of the data.
+    ///
+    /// Bytes that don't decode to anything are skipped this many at a time.
+    fn alignment(&self) -> usize {
+        1
+    }
+
+    /// Decodes the character at the start of `bytes`, along with how many
+    /// bytes it takes.
+    ///
+    /// Returns `None` if the bytes aren't a character, or if `bytes` ends
+    /// before the character does.
+    fn decode(&self, bytes: &[u8]) -> Option<(char, usize)>;
+
+    /// Encodes `c`, returning `None` if the [`Encoding`] can't represent it.
+    ///
+    /// [`Encoding`]: trait.Encoding.html
+    fn encode(&self, c: char) -> Option<Vec<u8>>;
+}
+
+/// What is shown for a byte in the character pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    /// The first byte of a printable character.
+    Char(char),
+    /// A byte after the first one of a character.
+    Continuation,
+    /// A byte that isn't part of a printable character.
+    Invalid,
+}
+
+impl Glyph {
+    /// Returns whether the [`Glyph`] is part of a printable character.
+    ///
+    /// [`Glyph`]: enum.Glyph.html
+    pub fn is_printable(&self) -> bool {
+        *self != Glyph::Invalid
+    }
+}
+
+/// Decodes the bytes of `source` in `range` with `encoding`, returning a
+/// [`Glyph`] for each byte.
+///
+/// Characters are decoded from a few bytes before the range, so one that
+/// starts before it is continued in it. Control characters are invalid.
+///
+/// [`Glyph`]: enum.Glyph.html
+pub fn glyphs(encoding: &dyn Encoding, source: &dyn ByteSource, range: Range<usize>) -> Vec<Glyph> {
+    let alignment = encoding.alignment().max(1);
+    let lookaround = encoding.max_len().max(1) - 1;
+    let start = range.start.saturating_sub(lookaround);
+    let start = start - start % alignment;
+    let end = (range.end + lookaround).min(source.len()).max(start);
+    let mut bytes = vec![0; end - start];
+    let read_count = source.read(start, &mut bytes);
+    let mut glyphs = Vec::with_capacity(read_count);
+
+    bytes.truncate(read_count);
+
+    while glyphs.len() < bytes.len() {
+        let rest = &bytes[glyphs.len()..];
+        let (glyph, len) = match encoding.decode(rest) {
+            Some((c, len)) if !c.is_control() => (Glyph::Char(c), len),
+            Some((_, len)) => (Glyph::Invalid, len),
+            None => (Glyph::Invalid, alignment),
+        };
+        let len = len.max(1).min(rest.len());
+
+        glyphs.push(glyph);
+
+        for _ in 1..len {
+            glyphs.push(match glyph {
+                Glyph::Char(_) => Glyph::Continuation,
+                _ => Glyph::Invalid,
+            });
+        }
+    }
+
+    glyphs.truncate(range.end.saturating_sub(start));
+    glyphs.drain(..(range.start - start).min(glyphs.len()));
+    glyphs
+}
+
+/// The [`Encoding`]s that come with the crate.
+///
+/// [`Encoding`]: trait.Encoding.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    /// Printable 7-bit ASCII.
+    Ascii,
+    /// ISO-8859-1, or Latin-1.
+    Latin1,
+    /// Windows-1252, Latin-1 with printable characters in the C1 range.
+    Windows1252,
+    /// Code page 437 of the IBM PC, with its symbols and box-drawing
+    /// characters.
+    Cp437,
+    /// EBCDIC, as code page 37.
+    Ebcdic,
+    /// UTF-8.
+    Utf8,
+    /// UTF-16, little endian.
+    Utf16Le,
+    /// UTF-16, big endian.
+    Utf16Be,
+}
+
+impl Builtin {
+    /// Every [`Builtin`] encoding.
+    ///
+    /// [`Builtin`]: enum.Builtin.html
+    pub const ALL: [Builtin; 8] = [
+        Builtin::Ascii,
+        Builtin::Latin1,
+        Builtin::Windows1252,
+        Builtin::Cp437,
+        Builtin::Ebcdic,
+        Builtin::Utf8,
+        Builtin::Utf16Le,
+        Builtin::Utf16Be,
+    ];
+
+    /// Maps a byte of a single-byte encoding to its character.
+    fn decode_byte(self, byte: u8) -> Option<char> {
+        match self {
+            Builtin::Ascii if byte.is_ascii() => Some(byte as char),
+            Builtin::Ascii => None,
+            Builtin::Latin1 => Some(byte as char),
+            Builtin::Windows1252 => match byte {
+                0x80..=0x9F => WINDOWS_1252[byte as usize - 0x80],
+                _ => Some(byte as char),
+            },
+            Builtin::Cp437 => match byte {
+                0x00..=0x1F => Some(CP437_LOW[byte as usize]),
+                0x7F => Some('\u{2302}'),
+                0x80..=0xFF => Some(CP437_HIGH[byte as usize - 0x80]),
+                _ => Some(byte as char),
+            },
+            Builtin::Ebcdic => Some(EBCDIC[byte as usize] as char),
+            Builtin::Utf8 | Builtin::Utf16Le | Builtin::Utf16Be => None,
+        }
+    }
+
+    fn utf16_endianness(self) -> Endianness {
+        match self {
+            Builtin::Utf16Be => Endianness::Big,
+            _ => Endianness::Little,
+        }
+    }
+}
+
+impl Default for Builtin {
+    fn default() -> Self {
+        Builtin::Ascii
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Encoding for Builtin {
+    fn name(&self) -> &str {
+        match self {
+            Builtin::Ascii => "ASCII",
+            Builtin::Latin1 => "ISO-8859-1",
+            Builtin::Windows1252 => "Windows-1252",
+            Builtin::Cp437 => "CP437",
+            Builtin::Ebcdic => "EBCDIC",
+            Builtin::Utf8 => "UTF-8",
+            Builtin::Utf16Le => "UTF-16LE",
+            Builtin::Utf16Be => "UTF-16BE",
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        match self {
+            Builtin::Utf8 | Builtin::Utf16Le | Builtin::Utf16Be => 4,
+            _ => 1,
+        }
+    }
+
+    fn alignment(&self) -> usize {
+        match self {
+            Builtin::Utf16Le | Builtin::Utf16Be => 2,
+            _ => 1,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<(char, usize)> {
+        match self {
+            Builtin::Utf8 => {
+                let len = match bytes.first()? {
+                    0x00..=0x7F => 1,
+                    0xC2..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF4 => 4,
+                    _ => return None,
+                };
+                let c = std::str::from_utf8(bytes.get(..len)?).ok()?.chars().next()?;
+
+                Some((c, len))
+            }
+            Builtin::Utf16Le | Builtin::Utf16Be => {
+                let endianness = self.utf16_endianness();
+                let unit = |i: usize| -> Option<u16> {
+                    let pair = [*bytes.get(i)?, *bytes.get(i + 1)?];
+
+                    Some(match endianness {
+                        Endianness::Little => u16::from_le_bytes(pair),
+                        Endianness::Big => u16::from_be_bytes(pair),
+                    })
+                };
+                let first = unit(0)?;
+
+                match first {
+                    0xD800..=0xDBFF => {
+                        let units = [first, unit(2)?];
+                        let c = std::char::decode_utf16(units.iter().cloned()).next()?.ok()?;
+
+                        Some((c, 4))
+                    }
+                    _ => std::char::from_u32(first as u32).map(|c| (c, 2)),
+                }
+            }
+            _ => self.decode_byte(*bytes.first()?).map(|c| (c, 1)),
+        }
+    }
+
+    fn encode(&self, c: char) -> Option<Vec<u8>> {
+        match self {
+            Builtin::Utf8 => Some(c.to_string().into_bytes()),
+            Builtin::Utf16Le | Builtin::Utf16Be => {
+                let endianness = self.utf16_endianness();
+                let mut units = [0; 2];
+
+                Some(c.encode_utf16(&mut units).iter().flat_map(|unit| match endianness {
+                    Endianness::Little => unit.to_le_bytes(),
+                    Endianness::Big => unit.to_be_bytes(),
+                }).collect())
+            }
+            _ => (0..=255u8).find(|byte| self.decode_byte(*byte) == Some(c)).map(|byte| vec![byte]),
+        }
+    }
+}
+
+/// The printable characters of Windows-1252 in `0x80..=0x9F`.
+const WINDOWS_1252: [Option<char>; 32] = [
+    Some('€'), None, Some('‚'), Some('ƒ'), Some('„'), Some('…'), Some('†'), Some('‡'),
+    Some('ˆ'), Some('‰'), Some('Š'), Some('‹'), Some('Œ'), None, Some('Ž'), None,
+    None, Some('‘'), Some('’'), Some('“'), Some('”'), Some('•'), Some('–'), Some('—'),
+    Some('˜'), Some('™'), Some('š'), Some('›'), Some('œ'), None, Some('ž'), Some('Ÿ'),
+];
+
+/// The symbols of code page 437 in `0x00..=0x1F`.
+const CP437_LOW: [char; 32] = [
+    '\u{0}', '☺', '☻', '♥', '♦', '♣', '♠', '•',
+    '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼',
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨',
+    '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+];
+
+/// The characters of code page 437 in `0x80..=0xFF`.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç',
+    'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù',
+    'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º',
+    '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖',
+    '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟',
+    '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫',
+    '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ',
+    'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈',
+    '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+/// The Latin-1 byte of each EBCDIC byte, as in code page 37.
+const EBCDIC: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9C, 0x09, 0x86, 0x7F, 0x97, 0x8D, 0x8E, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+    0x10, 0x11, 0x12, 0x13, 0x9D, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8F, 0x1C, 0x1D, 0x1E, 0x1F,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0A, 0x17, 0x1B, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9A, 0x9B, 0x14, 0x15, 0x9E, 0x1A,
+    0x20, 0xA0, 0xE2, 0xE4, 0xE0, 0xE1, 0xE3, 0xE5, 0xE7, 0xF1, 0xA2, 0x2E, 0x3C, 0x28, 0x2B, 0x7C,
+    0x26, 0xE9, 0xEA, 0xEB, 0xE8, 0xED, 0xEE, 0xEF, 0xEC, 0xDF, 0x21, 0x24, 0x2A, 0x29, 0x3B, 0xAC,
+    0x2D, 0x2F, 0xC2, 0xC4, 0xC0, 0xC1, 0xC3, 0xC5, 0xC7, 0xD1, 0xA6, 0x2C, 0x25, 0x5F, 0x3E, 0x3F,
+    0xF8, 0xC9, 0xCA, 0xCB, 0xC8, 0xCD, 0xCE, 0xCF, 0xCC, 0x60, 0x3A, 0x23, 0x40, 0x27, 0x3D, 0x22,
+    0xD8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xAB, 0xBB, 0xF0, 0xFD, 0xFE, 0xB1,
+    0xB0, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F, 0x70, 0x71, 0x72, 0xAA, 0xBA, 0xE6, 0xB8, 0xC6, 0xA4,
+    0xB5, 0x7E, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0xA1, 0xBF, 0xD0, 0xDD, 0xDE, 0xAE,
+    0x5E, 0xA3, 0xA5, 0xB7, 0xA9, 0xA7, 0xB6, 0xBC, 0xBD, 0xBE, 0x5B, 0x5D, 0xAF, 0xA8, 0xB4, 0xD7,
+    0x7B, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xAD, 0xF4, 0xF6, 0xF2, 0xF3, 0xF5,
+    0x7D, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F, 0x50, 0x51, 0x52, 0xB9, 0xFB, 0xFC, 0xF9, 0xFA, 0xFF,
+    0x5C, 0xF7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0xB2, 0xD4, 0xD6, 0xD2, 0xD3, 0xD5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xB3, 0xDB, 0xDC, 0xD9, 0xDA, 0x9F,
+];
+
+#[cfg(test)]
+mod test {
+    use super::{glyphs, Builtin, Encoding, Glyph};
+
+    #[test]
+    fn test_single_byte() {
+        assert_eq!(Builtin::Ascii.decode(b"A"), Some(('A', 1)));
+        assert_eq!(Builtin::Ascii.decode(&[0xC9]), None);
+        assert_eq!(Builtin::Latin1.decode(&[0xC9]), Some(('\u{C9}', 1)));
+        assert_eq!(Builtin::Windows1252.decode(&[0x80]), Some(('\u{20AC}', 1)));
+        assert_eq!(Builtin::Windows1252.decode(&[0x81]), None);
+        assert_eq!(Builtin::Cp437.decode(&[0xC9]), Some(('\u{2554}', 1)));
+        assert_eq!(Builtin::Ebcdic.decode(&[0xC1]), Some(('A', 1)));
+        assert_eq!(Builtin::Ebcdic.encode('0'), Some(vec![0xF0]));
+        assert_eq!(Builtin::Cp437.encode('\u{2554}'), Some(vec![0xC9]));
+        assert_eq!(Builtin::Ascii.encode('\u{C9}'), None);
+    }
+
+    #[test]
+    fn test_multi_byte() {
+        assert_eq!(Builtin::Utf8.decode("\u{E9}".as_bytes()), Some(('\u{E9}', 2)));
+        assert_eq!(Builtin::Utf8.decode(&[0xC3]), None);
+        assert_eq!(Builtin::Utf16Le.decode(&[0x41, 0x00]), Some(('A', 2)));
+        assert_eq!(Builtin::Utf16Be.decode(&[0xD8, 0x3D, 0xDE, 0x00]), Some(('\u{1F600}', 4)));
+        assert_eq!(Builtin::Utf16Be.encode('\u{1F600}'), Some(vec![0xD8, 0x3D, 0xDE, 0x00]));
+        assert_eq!(Builtin::Utf16Le.encode('A'), Some(vec![0x41, 0x00]));
+    }
+
+    #[test]
+    fn test_glyphs() {
+        let data = "a\u{E9}\u{20AC}\n".as_bytes().to_vec();
+
+        assert_eq!(glyphs(&Builtin::Utf8, &data, 0..data.len()), vec![
+            Glyph::Char('a'),
+            Glyph::Char('\u{E9}'),
+            Glyph::Continuation,
+            Glyph::Char('\u{20AC}'),
+            Glyph::Continuation,
+            Glyph::Continuation,
+            Glyph::Invalid,
+        ]);
+
+        // A character started before the range continues in it
+        assert_eq!(glyphs(&Builtin::Utf8, &data, 4..6), vec![Glyph::Continuation; 2]);
+        assert_eq!(glyphs(&Builtin::Utf16Le, &data, 1..4), vec![
+            Glyph::Continuation,
+            Glyph::Char('\u{E2A9}'),
+            Glyph::Continuation,
+        ]);
+    }
+}
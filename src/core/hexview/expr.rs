@@ -0,0 +1,280 @@
+//! Parsing of offset expressions, like the ones typed to go somewhere in an
+//! [`Hexview`].
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+use std::{error::Error, fmt, iter::Peekable, str::CharIndices};
+
+/// An error found while parsing an offset expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    /// There was nothing to parse.
+    Empty,
+    /// A number was malformed, like `0xZZ`.
+    InvalidNumber(String),
+    /// A character that doesn't belong in an expression was found at the
+    /// given byte index.
+    UnexpectedChar(usize, char),
+    /// The expression ended while something else was expected.
+    UnexpectedEnd,
+    /// Something was divided by zero.
+    DivisionByZero,
+    /// The result, or something on the way to it, was too big.
+    Overflow,
+    /// The result was before the start of the data.
+    Negative,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::Empty => write!(f, "empty expression"),
+            ExprError::InvalidNumber(number) => write!(f, "invalid number `{}`", number),
+            ExprError::UnexpectedChar(index, c) => {
+                write!(f, "unexpected `{}` at position {}", c, index)
+            }
+            ExprError::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            ExprError::DivisionByZero => write!(f, "division by zero"),
+            ExprError::Overflow => write!(f, "offset is too big"),
+            ExprError::Negative => write!(f, "offset is negative"),
+        }
+    }
+}
+
+impl Error for ExprError {}
+
+/// Parses an offset expression, relative to the `current` offset when it
+/// starts with a sign.
+///
+/// Numbers are decimal unless prefixed with `0x`, `0o` or `0b`, and may have
+/// `_` separators. They can be combined with `+`, `-`, `*`, `/` and
+/// parentheses, e.g. `0x4000 + 3*512`. An expression starting with a sign,
+/// like `+0x20` or `-16 + 4`, is added to `current` instead.
+pub fn parse_offset(input: &str, current: usize) -> Result<usize, ExprError> {
+    let expression = input.trim();
+    let skipped = input.len() - input.trim_start().len();
+
+    if expression.is_empty() {
+        return Err(ExprError::Empty);
+    }
+
+    let relative = expression.starts_with('+') || expression.starts_with('-');
+    let value = parse_expression(expression).map_err(|error| match error {
+        // Positions are in what was typed, not in what was trimmed from it
+        ExprError::UnexpectedChar(index, c) => ExprError::UnexpectedChar(skipped + index, c),
+        error => error,
+    })?;
+
+    let offset = if relative {
+        (current as i128).checked_add(value).ok_or(ExprError::Overflow)?
+    } else {
+        value
+    };
+
+    if offset < 0 {
+        Err(ExprError::Negative)
+    } else if offset > usize::MAX as i128 {
+        Err(ExprError::Overflow)
+    } else {
+        Ok(offset as usize)
+    }
+}
+
+/// Parses an integer with an optional sign, which may have a `0x`, `0o` or
+/// `0b` prefix and `_` separators.
+pub(crate) fn parse_integer(text: &str) -> Option<i128> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text.trim_start_matches('+')),
+    };
+    let digits: String = text.chars().filter(|c| *c != '_').collect();
+    let (radix, digits) = match digits.get(0..2) {
+        Some("0x") | Some("0X") => (16, &digits[2..]),
+        Some("0o") | Some("0O") => (8, &digits[2..]),
+        Some("0b") | Some("0B") => (2, &digits[2..]),
+        _ => (10, &digits[..]),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    let value = i128::from_str_radix(digits, radix).ok()?;
+
+    Some(if negative { -value } else { value })
+}
+
+/// Parses the whole of a trimmed expression, where a leading sign is a unary
+/// one.
+fn parse_expression(expression: &str) -> Result<i128, ExprError> {
+    let mut parser = Parser {
+        chars: expression.char_indices().peekable(),
+        input: expression,
+    };
+    let value = parser.expression()?;
+
+    parser.skip_whitespace();
+
+    match parser.chars.next() {
+        Some((index, c)) => Err(ExprError::UnexpectedChar(index, c)),
+        None => Ok(value),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    input: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// `expression = term (('+' | '-') term)*`
+    fn expression(&mut self) -> Result<i128, ExprError> {
+        let mut value = self.term()?;
+
+        loop {
+            self.skip_whitespace();
+
+            value = match self.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    value.checked_add(self.term()?)
+                }
+                Some('-') => {
+                    self.chars.next();
+                    value.checked_sub(self.term()?)
+                }
+                _ => return Ok(value),
+            }.ok_or(ExprError::Overflow)?;
+        }
+    }
+
+    /// `term = factor (('*' | '/') factor)*`
+    fn term(&mut self) -> Result<i128, ExprError> {
+        let mut value = self.factor()?;
+
+        loop {
+            self.skip_whitespace();
+
+            value = match self.peek() {
+                Some('*') => {
+                    self.chars.next();
+                    value.checked_mul(self.factor()?).ok_or(ExprError::Overflow)?
+                }
+                Some('/') => {
+                    self.chars.next();
+                    value.checked_div(self.factor()?).ok_or(ExprError::DivisionByZero)?
+                }
+                _ => return Ok(value),
+            };
+        }
+    }
+
+    /// `factor = ('+' | '-') factor | '(' expression ')' | number`
+    fn factor(&mut self) -> Result<i128, ExprError> {
+        self.skip_whitespace();
+
+        match self.chars.peek().copied() {
+            Some((_, '+')) => {
+                self.chars.next();
+                self.factor()
+            }
+            Some((_, '-')) => {
+                self.chars.next();
+                self.factor()?.checked_neg().ok_or(ExprError::Overflow)
+            }
+            Some((_, '(')) => {
+                self.chars.next();
+                let value = self.expression()?;
+
+                self.skip_whitespace();
+
+                match self.chars.next() {
+                    Some((_, ')')) => Ok(value),
+                    Some((index, c)) => Err(ExprError::UnexpectedChar(index, c)),
+                    None => Err(ExprError::UnexpectedEnd),
+                }
+            }
+            Some((start, c)) if c.is_ascii_digit() => self.number(start),
+            Some((index, c)) => Err(ExprError::UnexpectedChar(index, c)),
+            None => Err(ExprError::UnexpectedEnd),
+        }
+    }
+
+    fn number(&mut self, start: usize) -> Result<i128, ExprError> {
+        let mut end = start;
+
+        while let Some((index, c)) = self.chars.peek().copied() {
+            if !c.is_ascii_alphanumeric() && c != '_' {
+                break;
+            }
+
+            end = index + c.len_utf8();
+            self.chars.next();
+        }
+
+        let number = &self.input[start..end];
+        let digits: String = number.chars().filter(|c| *c != '_').collect();
+        let (radix, digits) = match digits.get(0..2) {
+            Some("0x") | Some("0X") => (16, &digits[2..]),
+            Some("0o") | Some("0O") => (8, &digits[2..]),
+            Some("0b") | Some("0B") => (2, &digits[2..]),
+            _ => (10, &digits[..]),
+        };
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return Err(ExprError::InvalidNumber(number.into()));
+        }
+
+        i128::from_str_radix(digits, radix).map_err(|_| ExprError::Overflow)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(true) = self.peek().map(char::is_whitespace) {
+            self.chars.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_offset, ExprError};
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(parse_offset("0x1F40", 0), Ok(0x1F40));
+        assert_eq!(parse_offset("8000", 0), Ok(8000));
+        assert_eq!(parse_offset("0b1010_1010", 0), Ok(0b1010_1010));
+        assert_eq!(parse_offset("0o17", 0), Ok(0o17));
+        assert_eq!(parse_offset("0xZZ", 0), Err(ExprError::InvalidNumber("0xZZ".into())));
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(parse_offset("+0x20", 0x100), Ok(0x120));
+        assert_eq!(parse_offset("-16", 0x100), Ok(0xF0));
+        assert_eq!(parse_offset("-0x200", 0x100), Err(ExprError::Negative));
+        assert_eq!(parse_offset("-16 + 4", 0x100), Ok(0xF4));
+        assert_eq!(parse_offset("-(16 + 4)", 0x100), Ok(0xEC));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(parse_offset("0x4000 + 3*512", 0), Ok(0x4000 + 3 * 512));
+        assert_eq!(parse_offset("(1 + 2) * 3 - 10 / 2", 0), Ok(4));
+        assert_eq!(parse_offset(" 4 - -4 ", 0), Ok(8));
+        assert_eq!(parse_offset("1 / 0", 0), Err(ExprError::DivisionByZero));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_offset("", 0), Err(ExprError::Empty));
+        assert_eq!(parse_offset("+", 0), Err(ExprError::UnexpectedEnd));
+        assert_eq!(parse_offset("(1 + 2", 0), Err(ExprError::UnexpectedEnd));
+        assert_eq!(parse_offset("1 $ 2", 0), Err(ExprError::UnexpectedChar(2, '$')));
+        assert_eq!(parse_offset("  -1 $", 0), Err(ExprError::UnexpectedChar(5, '$')));
+    }
+}
@@ -0,0 +1,128 @@
+//! A [`ByteSource`] backed by a memory-mapped file.
+//!
+//! [`ByteSource`]: ../source/trait.ByteSource.html
+
+use memmap2::Mmap;
+use std::{
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+};
+use super::source::{read_slice, ByteSource};
+
+/// A read-only [`ByteSource`] that maps a file into memory.
+///
+/// The operating system only pages in the parts of the file that are actually read, so opening
+/// is instant regardless of the file size. Edits are meant to be made through a [`PieceTable`]
+/// wrapping it, which never writes to the file.
+///
+/// # Warning
+///
+/// The file must not be truncated or modified by this or any other process while it's mapped.
+/// Changes would show up in the source behind the widget's back, and reading a page that was
+/// truncated away raises `SIGBUS` and aborts the whole program.
+///
+/// [`ByteSource`]: ../source/trait.ByteSource.html
+/// [`PieceTable`]: ../piece_table/struct.PieceTable.html
+#[derive(Debug)]
+pub struct FileSource {
+    path: PathBuf,
+    // Empty files can't be mapped
+    map: Option<Mmap>,
+}
+
+impl FileSource {
+    /// Maps the file at `path` read-only.
+    ///
+    /// # Warning
+    ///
+    /// The file must not be truncated or modified while the returned [`FileSource`] is alive,
+    /// see the [type documentation] for details.
+    ///
+    /// [`FileSource`]: struct.FileSource.html
+    /// [type documentation]: struct.FileSource.html#warning
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        let map = if len == 0 {
+            None
+        } else {
+            // SAFETY: Mapping a file is only sound as long as nobody truncates or writes to it
+            // while it's mapped, which would make reads fault with SIGBUS or observe the bytes
+            // changing under a shared reference. Nothing in this crate ever writes to the file,
+            // and other processes can't be prevented from doing so, so that invariant is
+            // documented on `FileSource` and `State::open_file` and left to the caller.
+            Some(unsafe { Mmap::map(&file)? })
+        };
+
+        Ok(Self {
+            path: path.to_owned(),
+            map,
+        })
+    }
+
+    /// Returns the path of the mapped file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or(&[])
+    }
+}
+
+impl ByteSource for FileSource {
+    fn len(&self) -> usize {
+        self.bytes().len()
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize {
+        read_slice(self.bytes(), offset, buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ByteSource, FileSource};
+    use std::io::Write;
+
+    fn temp_file(contents: &[u8]) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents).unwrap();
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_read() {
+        let file = temp_file(b"\x00\x01\x02\x03\x04\x05");
+        let source = FileSource::open(file.path()).unwrap();
+        let mut buf = [0; 4];
+
+        assert_eq!(source.len(), 6);
+        assert_eq!(source.read(4, &mut buf), 2);
+        assert_eq!(&buf[..2], b"\x04\x05");
+        assert_eq!(source.read(6, &mut buf), 0);
+    }
+
+    #[test]
+    fn test_read_only() {
+        let file = temp_file(b"abcd");
+        let mut source = FileSource::open(file.path()).unwrap();
+
+        assert!(!source.is_writable());
+        assert!(source.write(0, b"x").is_err());
+        assert_eq!(std::fs::read(file.path()).unwrap(), b"abcd");
+    }
+
+    #[test]
+    fn test_empty() {
+        let file = temp_file(b"");
+        let source = FileSource::open(file.path()).unwrap();
+
+        assert!(source.is_empty());
+        assert!(!source.is_writable());
+    }
+}
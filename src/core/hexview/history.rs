@@ -0,0 +1,197 @@
+//! Undo and redo of the changes made to an [`Hexview`].
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+use std::collections::VecDeque;
+use super::Edit;
+
+/// The default amount of groups of edits a [`History`] keeps.
+///
+/// [`History`]: struct.History.html
+pub const DEFAULT_LIMIT: usize = 1000;
+
+/// A record of edits that can be undone and redone.
+///
+/// Edits are kept in groups, which are undone and redone as a whole. A group
+/// can be left open so that consecutive edits, like typing, join it instead
+/// of starting a new one.
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+    limit: usize,
+    is_open: bool,
+}
+
+impl History {
+    /// Creates an empty [`History`] that keeps up to `limit` groups of edits.
+    ///
+    /// [`History`]: struct.History.html
+    pub fn new(limit: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+            is_open: false,
+        }
+    }
+
+    /// Records an edit, clearing everything that could be redone.
+    ///
+    /// If `merge` is set, the edit joins the last group as long as it's still
+    /// open and the edit continues where the last one left off. Either way,
+    /// the group is left open for the next edit only when `merge` is set.
+    pub fn record(&mut self, edit: Edit, merge: bool) {
+        self.redo.clear();
+
+        let joins_last = merge && self.is_open && self.undo.back()
+            .and_then(|group| group.last())
+            .map(|last| edit.offset == last.offset || edit.offset == last.offset + last.new.len())
+            .unwrap_or(false);
+
+        if joins_last {
+            self.undo.back_mut().unwrap().push(edit);
+        } else {
+            self.undo.push_back(vec![edit]);
+            self.truncate();
+        }
+
+        self.is_open = merge;
+    }
+
+    /// Records a group of edits that were made at once, clearing everything
+    /// that could be redone.
+    ///
+    /// The edits must be in the order they were made.
+    pub fn record_group(&mut self, edits: Vec<Edit>) {
+        if edits.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push_back(edits);
+        self.truncate();
+        self.is_open = false;
+    }
+
+    /// Closes the last group, so the next edit starts a new one.
+    pub fn close_group(&mut self) {
+        self.is_open = false;
+    }
+
+    /// Takes the last group of edits to be undone, moving it to the redo stack.
+    ///
+    /// The edits are returned in the order they were made, so they must be
+    /// reverted from last to first.
+    pub fn undo(&mut self) -> Option<&[Edit]> {
+        let group = self.undo.pop_back()?;
+        self.is_open = false;
+        self.redo.push(group);
+
+        self.redo.last().map(Vec::as_slice)
+    }
+
+    /// Takes the last undone group of edits, moving it back to the undo stack.
+    ///
+    /// The edits are returned in the order they were made.
+    pub fn redo(&mut self) -> Option<&[Edit]> {
+        let group = self.redo.pop()?;
+        self.is_open = false;
+        self.undo.push_back(group);
+
+        self.undo.back().map(Vec::as_slice)
+    }
+
+    /// Returns whether there's anything to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns whether there's anything to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns the amount of groups of edits kept.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Sets the amount of groups of edits kept, forgetting the oldest ones if
+    /// there are too many.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.truncate();
+        self.redo.truncate(limit);
+    }
+
+    /// Forgets every edit.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.is_open = false;
+    }
+
+    fn truncate(&mut self) {
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Edit, History};
+
+    fn typed(offset: usize, new: u8) -> Edit {
+        Edit { offset, old: Vec::new(), new: vec![new] }
+    }
+
+    #[test]
+    fn test_grouping() {
+        let mut history = History::default();
+
+        history.record(typed(0, b'a'), true);
+        history.record(typed(1, b'b'), true);
+        history.record(typed(5, b'c'), true);
+        history.close_group();
+        history.record(typed(6, b'd'), true);
+
+        assert_eq!(history.undo().map(<[Edit]>::len), Some(1));
+        assert_eq!(history.undo().map(<[Edit]>::len), Some(1));
+        assert_eq!(history.undo().map(<[Edit]>::len), Some(2));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo().map(<[Edit]>::len), Some(2));
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut history = History::default();
+
+        history.record(typed(0, b'a'), false);
+        history.undo();
+        history.record(typed(0, b'b'), false);
+
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut history = History::new(2);
+
+        for i in 0..3 {
+            history.record(typed(i * 2, b'a'), false);
+        }
+
+        assert_eq!(history.undo().map(|group| group[0].offset), Some(4));
+        assert_eq!(history.undo().map(|group| group[0].offset), Some(2));
+        assert!(history.undo().is_none());
+    }
+}
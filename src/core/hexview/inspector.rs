@@ -0,0 +1,629 @@
+//! Decoding and encoding of the values an [`Inspector`] shows.
+//!
+//! [`Inspector`]: ../../../native/inspector/struct.Inspector.html
+
+use super::{expr::parse_integer, Endianness};
+use std::{error::Error, fmt};
+
+/// The most bytes any [`Kind`] of value takes.
+///
+/// [`Kind`]: enum.Kind.html
+pub const MAX_SIZE: usize = 16;
+
+/// Seconds in a day.
+const DAY: i64 = 24 * 60 * 60;
+
+/// A way to interpret bytes as a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// An unsigned 8-bit integer.
+    U8,
+    /// A signed 8-bit integer.
+    I8,
+    /// An unsigned 16-bit integer.
+    U16,
+    /// A signed 16-bit integer.
+    I16,
+    /// An unsigned 32-bit integer.
+    U32,
+    /// A signed 32-bit integer.
+    I32,
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A signed 64-bit integer.
+    I64,
+    /// A 32-bit floating point number.
+    F32,
+    /// A 64-bit floating point number.
+    F64,
+    /// An unsigned LEB128 integer, up to 64 bits.
+    Uleb128,
+    /// A signed LEB128 integer, up to 64 bits.
+    Sleb128,
+    /// Seconds since the Unix epoch, as a signed 32-bit integer.
+    UnixTime32,
+    /// Seconds since the Unix epoch, as a signed 64-bit integer.
+    UnixTime64,
+    /// A GUID, whose first three groups are swapped in little endian like
+    /// Microsoft stores them.
+    Guid,
+    /// The bits of a byte.
+    Binary,
+}
+
+impl Kind {
+    /// Every [`Kind`], in the order an [`Inspector`] shows them.
+    ///
+    /// [`Kind`]: enum.Kind.html
+    /// [`Inspector`]: ../../../native/inspector/struct.Inspector.html
+    pub const ALL: [Kind; 16] = [
+        Kind::U8,
+        Kind::I8,
+        Kind::U16,
+        Kind::I16,
+        Kind::U32,
+        Kind::I32,
+        Kind::U64,
+        Kind::I64,
+        Kind::F32,
+        Kind::F64,
+        Kind::Uleb128,
+        Kind::Sleb128,
+        Kind::UnixTime32,
+        Kind::UnixTime64,
+        Kind::Guid,
+        Kind::Binary,
+    ];
+
+    /// Returns a short name for the [`Kind`].
+    ///
+    /// [`Kind`]: enum.Kind.html
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::U8 => "u8",
+            Kind::I8 => "i8",
+            Kind::U16 => "u16",
+            Kind::I16 => "i16",
+            Kind::U32 => "u32",
+            Kind::I32 => "i32",
+            Kind::U64 => "u64",
+            Kind::I64 => "i64",
+            Kind::F32 => "f32",
+            Kind::F64 => "f64",
+            Kind::Uleb128 => "ULEB128",
+            Kind::Sleb128 => "SLEB128",
+            Kind::UnixTime32 => "time32",
+            Kind::UnixTime64 => "time64",
+            Kind::Guid => "GUID",
+            Kind::Binary => "binary",
+        }
+    }
+
+    /// Returns whether the [`Endianness`] changes how the [`Kind`] is read.
+    ///
+    /// [`Endianness`]: ../template/enum.Endianness.html
+    /// [`Kind`]: enum.Kind.html
+    pub fn has_endianness(self) -> bool {
+        !matches!(
+            self,
+            Kind::U8 | Kind::I8 | Kind::Uleb128 | Kind::Sleb128 | Kind::Binary
+        )
+    }
+
+    /// The amount of bytes the [`Kind`] takes, or `None` if it depends on the
+    /// value.
+    ///
+    /// [`Kind`]: enum.Kind.html
+    pub fn size(self) -> Option<usize> {
+        match self {
+            Kind::U8 | Kind::I8 | Kind::Binary => Some(1),
+            Kind::U16 | Kind::I16 => Some(2),
+            Kind::U32 | Kind::I32 | Kind::F32 | Kind::UnixTime32 => Some(4),
+            Kind::U64 | Kind::I64 | Kind::F64 | Kind::UnixTime64 => Some(8),
+            Kind::Guid => Some(16),
+            Kind::Uleb128 | Kind::Sleb128 => None,
+        }
+    }
+
+    /// Reads a value from the start of `bytes`, returning `None` if there
+    /// aren't enough of them or they don't make a valid value.
+    pub fn decode(self, bytes: &[u8], endianness: Endianness) -> Option<String> {
+        let unsigned = |size| read_uint(bytes, size, endianness);
+        let signed = |size| unsigned(size).map(|value| sign_extend(value, size));
+
+        Some(match self {
+            Kind::U8 => unsigned(1)?.to_string(),
+            Kind::U16 => unsigned(2)?.to_string(),
+            Kind::U32 => unsigned(4)?.to_string(),
+            Kind::U64 => unsigned(8)?.to_string(),
+            Kind::I8 => signed(1)?.to_string(),
+            Kind::I16 => signed(2)?.to_string(),
+            Kind::I32 => signed(4)?.to_string(),
+            Kind::I64 => signed(8)?.to_string(),
+            Kind::F32 => f32::from_bits(unsigned(4)? as u32).to_string(),
+            Kind::F64 => f64::from_bits(unsigned(8)?).to_string(),
+            Kind::Uleb128 => read_leb128(bytes, false)?.to_string(),
+            Kind::Sleb128 => read_leb128(bytes, true)?.to_string(),
+            Kind::UnixTime32 => format_time(signed(4)?)?,
+            Kind::UnixTime64 => format_time(signed(8)?)?,
+            Kind::Guid => {
+                let bytes = bytes.get(..16)?;
+                let mut guid = [0; 16];
+                guid.copy_from_slice(bytes);
+
+                if endianness == Endianness::Little {
+                    swap_guid(&mut guid);
+                }
+
+                format!(
+                    "{:02X}{:02X}{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-{:02X}{:02X}-\
+                     {:02X}{:02X}{:02X}{:02X}{:02X}{:02X}",
+                    guid[0], guid[1], guid[2], guid[3], guid[4], guid[5], guid[6], guid[7],
+                    guid[8], guid[9], guid[10], guid[11], guid[12], guid[13], guid[14], guid[15],
+                )
+            }
+            Kind::Binary => format!("{:08b}", bytes.first()?),
+        })
+    }
+
+    /// Turns `text` into the bytes of a value, written the same way
+    /// [`decode`] shows it.
+    ///
+    /// Integers can also be written with a `0x`, `0o` or `0b` prefix, and
+    /// times as seconds.
+    ///
+    /// [`decode`]: #method.decode
+    pub fn encode(self, text: &str, endianness: Endianness) -> Result<Vec<u8>, ValueError> {
+        let text = text.trim();
+        let integer = |min: i128, max: i128| {
+            let value = parse_integer(text).ok_or(ValueError::Invalid)?;
+
+            if value < min || value > max {
+                Err(ValueError::OutOfRange)
+            } else {
+                Ok(value)
+            }
+        };
+        let unsigned = |size: usize| -> Result<Vec<u8>, ValueError> {
+            let max = u64::MAX as i128 >> (64 - size * 8);
+
+            Ok(write_uint(integer(0, max)? as u64, size, endianness))
+        };
+        let signed = |size: usize| -> Result<Vec<u8>, ValueError> {
+            let max = i64::MAX as i128 >> (64 - size * 8);
+
+            Ok(write_uint(integer(-max - 1, max)? as u64, size, endianness))
+        };
+        let time = |size: usize| -> Result<Vec<u8>, ValueError> {
+            let seconds = match parse_time(text) {
+                Some(seconds) => seconds as i128,
+                None => parse_integer(text).ok_or(ValueError::Invalid)?,
+            };
+            let max = i64::MAX as i128 >> (64 - size * 8);
+
+            if seconds < -max - 1 || seconds > max {
+                return Err(ValueError::OutOfRange);
+            }
+
+            Ok(write_uint(seconds as u64, size, endianness))
+        };
+
+        match self {
+            Kind::U8 => unsigned(1),
+            Kind::U16 => unsigned(2),
+            Kind::U32 => unsigned(4),
+            Kind::U64 => unsigned(8),
+            Kind::I8 => signed(1),
+            Kind::I16 => signed(2),
+            Kind::I32 => signed(4),
+            Kind::I64 => signed(8),
+            Kind::F32 => {
+                let value: f32 = text.parse().map_err(|_| ValueError::Invalid)?;
+
+                Ok(write_uint(value.to_bits() as u64, 4, endianness))
+            }
+            Kind::F64 => {
+                let value: f64 = text.parse().map_err(|_| ValueError::Invalid)?;
+
+                Ok(write_uint(value.to_bits(), 8, endianness))
+            }
+            Kind::Uleb128 => Ok(write_uleb128(integer(0, u64::MAX as i128)? as u64)),
+            Kind::Sleb128 => {
+                Ok(write_sleb128(integer(i64::MIN as i128, i64::MAX as i128)? as i64))
+            }
+            Kind::UnixTime32 => time(4),
+            Kind::UnixTime64 => time(8),
+            Kind::Guid => {
+                let digits: String = text
+                    .trim_start_matches('{')
+                    .trim_end_matches('}')
+                    .chars()
+                    .filter(|c| *c != '-')
+                    .collect();
+
+                if digits.len() != 32 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(ValueError::Invalid);
+                }
+
+                let mut guid = [0; 16];
+
+                for (i, byte) in guid.iter_mut().enumerate() {
+                    *byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16)
+                        .map_err(|_| ValueError::Invalid)?;
+                }
+
+                if endianness == Endianness::Little {
+                    swap_guid(&mut guid);
+                }
+
+                Ok(guid.to_vec())
+            }
+            Kind::Binary => {
+                if text.is_empty() || text.len() > 8 {
+                    return Err(ValueError::Invalid);
+                }
+
+                u8::from_str_radix(text, 2).map(|byte| vec![byte]).map_err(|_| ValueError::Invalid)
+            }
+        }
+    }
+
+    /// Writes a value from `text` like [`encode`], to replace the value at
+    /// the start of `current`.
+    ///
+    /// The value can't take more bytes than `current` has. A LEB128 value
+    /// also can't be longer than the one already there, and is padded to its
+    /// length so the bytes after it are left as they were.
+    ///
+    /// [`encode`]: #method.encode
+    pub fn encode_over(
+        self,
+        text: &str,
+        endianness: Endianness,
+        current: &[u8],
+    ) -> Result<Vec<u8>, ValueError> {
+        let mut bytes = self.encode(text, endianness)?;
+        let signed = match self {
+            Kind::Uleb128 => false,
+            Kind::Sleb128 => true,
+            _ if bytes.len() <= current.len() => return Ok(bytes),
+            _ => return Err(ValueError::OutOfRange),
+        };
+
+        // Without a valid value there, there's no length to keep
+        let len = match leb128_len(current) {
+            Some(len) => len,
+            None if bytes.len() <= current.len() => return Ok(bytes),
+            None => return Err(ValueError::OutOfRange),
+        };
+
+        if bytes.len() > len {
+            return Err(ValueError::OutOfRange);
+        }
+
+        pad_leb128(&mut bytes, len, signed);
+        Ok(bytes)
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// An error found while encoding a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueError {
+    /// The value couldn't be understood.
+    Invalid,
+    /// The value doesn't fit in its [`Kind`].
+    ///
+    /// [`Kind`]: enum.Kind.html
+    OutOfRange,
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueError::Invalid => write!(f, "invalid value"),
+            ValueError::OutOfRange => write!(f, "value out of range"),
+        }
+    }
+}
+
+impl Error for ValueError {}
+
+fn read_uint(bytes: &[u8], size: usize, endianness: Endianness) -> Option<u64> {
+    let bytes = bytes.get(..size)?;
+    let fold = |value: u64, byte: &u8| value << 8 | *byte as u64;
+
+    Some(match endianness {
+        Endianness::Little => bytes.iter().rev().fold(0, fold),
+        Endianness::Big => bytes.iter().fold(0, fold),
+    })
+}
+
+fn write_uint(value: u64, size: usize, endianness: Endianness) -> Vec<u8> {
+    let bytes = value.to_le_bytes();
+    let mut bytes = bytes[..size].to_vec();
+
+    if endianness == Endianness::Big {
+        bytes.reverse();
+    }
+
+    bytes
+}
+
+fn sign_extend(value: u64, size: usize) -> i64 {
+    let shift = 64 - size * 8;
+
+    ((value << shift) as i64) >> shift
+}
+
+/// Swaps the bytes of the first three groups of a GUID.
+fn swap_guid(guid: &mut [u8; 16]) {
+    guid[..4].reverse();
+    guid[4..6].reverse();
+    guid[6..8].reverse();
+}
+
+fn read_leb128(bytes: &[u8], signed: bool) -> Option<i128> {
+    let mut value: i128 = 0;
+
+    for (i, byte) in bytes.iter().take(10).enumerate() {
+        value |= ((byte & 0x7F) as i128) << (i * 7);
+
+        if byte & 0x80 == 0 {
+            let bits = (i + 1) * 7;
+
+            if signed && byte & 0x40 != 0 {
+                value -= 1 << bits;
+            }
+
+            let (min, max) = if signed {
+                (i64::MIN as i128, i64::MAX as i128)
+            } else {
+                (0, u64::MAX as i128)
+            };
+
+            return if value < min || value > max { None } else { Some(value) };
+        }
+    }
+
+    None
+}
+
+/// The length of the LEB128 value at the start of `bytes`, if it ends in
+/// them.
+fn leb128_len(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().take(10).position(|byte| byte & 0x80 == 0).map(|index| index + 1)
+}
+
+/// Pads an encoded LEB128 value to `len` bytes with continuation bytes that
+/// don't change it.
+fn pad_leb128(bytes: &mut Vec<u8>, len: usize, signed: bool) {
+    if bytes.len() >= len {
+        return;
+    }
+
+    let last = match bytes.last_mut() {
+        Some(last) => last,
+        None => return,
+    };
+
+    // Signed values are extended with their sign
+    let fill = if signed && *last & 0x40 != 0 { 0x7F } else { 0x00 };
+
+    *last |= 0x80;
+
+    while bytes.len() < len - 1 {
+        bytes.push(fill | 0x80);
+    }
+
+    bytes.push(fill);
+}
+
+fn write_uleb128(mut value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(mut value: i64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            bytes.push(byte);
+            return bytes;
+        }
+
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Formats seconds since the Unix epoch as an UTC date and time, if its year
+/// has four digits.
+fn format_time(seconds: i64) -> Option<String> {
+    let days = seconds.div_euclid(DAY);
+    let time = seconds.rem_euclid(DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    if !(0..=9999).contains(&year) {
+        return None;
+    }
+
+    Some(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+    ))
+}
+
+/// Parses an UTC date and time like `2021-01-31 23:59:59` into seconds since
+/// the Unix epoch.
+fn parse_time(text: &str) -> Option<i64> {
+    let mut parts = text.split(&[' ', 'T'][..]).filter(|part| !part.is_empty());
+    let date: Vec<i64> = parts.next()?.split('-').map(str::parse).collect::<Result<_, _>>().ok()?;
+    let time: Vec<i64> = match parts.next() {
+        Some(time) => time.split(':').map(str::parse).collect::<Result<_, _>>().ok()?,
+        None => vec![0, 0, 0],
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    match (date.as_slice(), time.as_slice()) {
+        ([year, month, day], [hours, minutes, seconds])
+            if (1..=12).contains(month)
+                && (1..=31).contains(day)
+                && (0..24).contains(hours)
+                && (0..60).contains(minutes)
+                && (0..60).contains(seconds) =>
+        {
+            Some(days_from_civil(*year, *month, *day) * DAY + hours * 3600 + minutes * 60 + seconds)
+        }
+        _ => None,
+    }
+}
+
+/// Converts days since the Unix epoch to a year, month and day.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Converts a year, month and day to days since the Unix epoch.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Endianness, Kind, ValueError};
+
+    #[test]
+    fn test_integers() {
+        let bytes = [0xFE, 0xFF, 0x00, 0x01];
+
+        assert_eq!(Kind::U16.decode(&bytes, Endianness::Little), Some("65534".into()));
+        assert_eq!(Kind::U16.decode(&bytes, Endianness::Big), Some("65279".into()));
+        assert_eq!(Kind::I16.decode(&bytes, Endianness::Little), Some("-2".into()));
+        assert_eq!(Kind::U32.decode(&bytes, Endianness::Big), Some("4278124545".into()));
+        assert_eq!(Kind::U64.decode(&bytes, Endianness::Little), None);
+        assert_eq!(Kind::Binary.decode(&bytes, Endianness::Little), Some("11111110".into()));
+
+        assert_eq!(Kind::I16.encode("-2", Endianness::Little), Ok(vec![0xFE, 0xFF]));
+        assert_eq!(Kind::U32.encode("0x01020304", Endianness::Big), Ok(vec![1, 2, 3, 4]));
+        assert_eq!(Kind::U8.encode("256", Endianness::Little), Err(ValueError::OutOfRange));
+        assert_eq!(Kind::I8.encode("-128", Endianness::Little), Ok(vec![0x80]));
+        assert_eq!(Kind::U8.encode("nope", Endianness::Little), Err(ValueError::Invalid));
+        assert_eq!(Kind::F32.encode("1.5", Endianness::Big), Ok(vec![0x3F, 0xC0, 0, 0]));
+        assert_eq!(Kind::Binary.encode("101", Endianness::Big), Ok(vec![5]));
+    }
+
+    #[test]
+    fn test_leb128() {
+        assert_eq!(Kind::Uleb128.decode(&[0xE5, 0x8E, 0x26], Endianness::Little), Some("624485".into()));
+        assert_eq!(Kind::Sleb128.decode(&[0xC0, 0xBB, 0x78], Endianness::Little), Some("-123456".into()));
+        assert_eq!(Kind::Uleb128.decode(&[0x80, 0x80], Endianness::Little), None);
+
+        assert_eq!(Kind::Uleb128.encode("624485", Endianness::Little), Ok(vec![0xE5, 0x8E, 0x26]));
+        assert_eq!(Kind::Sleb128.encode("-123456", Endianness::Little), Ok(vec![0xC0, 0xBB, 0x78]));
+        assert_eq!(Kind::Sleb128.encode("63", Endianness::Little), Ok(vec![0x3F]));
+        assert_eq!(Kind::Sleb128.encode("64", Endianness::Little), Ok(vec![0xC0, 0x00]));
+    }
+
+    #[test]
+    fn test_encode_over() {
+        let current = [0xE5, 0x8E, 0x26, 0x42];
+        let shorter = Kind::Uleb128.encode_over("1", Endianness::Little, &current).unwrap();
+
+        // The old value's continuation bytes don't leak into the next one
+        assert_eq!(shorter, vec![0x81, 0x80, 0x00]);
+        assert_eq!(Kind::Uleb128.decode(&shorter, Endianness::Little), Some("1".into()));
+
+        let negative = Kind::Sleb128.encode_over("-1", Endianness::Little, &current).unwrap();
+
+        assert_eq!(negative, vec![0xFF, 0xFF, 0x7F]);
+        assert_eq!(Kind::Sleb128.decode(&negative, Endianness::Little), Some("-1".into()));
+
+        assert_eq!(
+            Kind::Uleb128.encode_over("624485", Endianness::Little, &[0x01, 0x02]),
+            Err(ValueError::OutOfRange),
+        );
+        assert_eq!(
+            Kind::U32.encode_over("1", Endianness::Little, &[0, 0]),
+            Err(ValueError::OutOfRange),
+        );
+    }
+
+    #[test]
+    fn test_time() {
+        let bytes = [0x00, 0x00, 0x00, 0x80];
+
+        assert_eq!(
+            Kind::UnixTime32.decode(&bytes, Endianness::Little),
+            Some("1901-12-13 20:45:52".into()),
+        );
+        assert_eq!(
+            Kind::UnixTime32.encode("2021-01-31 23:59:59", Endianness::Big),
+            Ok(1_612_137_599u32.to_be_bytes().to_vec()),
+        );
+        assert_eq!(
+            Kind::UnixTime32.decode(&1_612_137_599u32.to_le_bytes(), Endianness::Little),
+            Some("2021-01-31 23:59:59".into()),
+        );
+        assert_eq!(Kind::UnixTime32.encode("2100-01-01", Endianness::Big), Err(ValueError::OutOfRange));
+    }
+
+    #[test]
+    fn test_guid() {
+        let bytes: Vec<u8> = (0..16).collect();
+        let little = "03020100-0504-0706-0809-0A0B0C0D0E0F";
+
+        assert_eq!(Kind::Guid.decode(&bytes, Endianness::Little), Some(little.into()));
+        assert_eq!(
+            Kind::Guid.decode(&bytes, Endianness::Big),
+            Some("00010203-0405-0607-0809-0A0B0C0D0E0F".into()),
+        );
+        assert_eq!(Kind::Guid.encode(&format!("{{{}}}", little), Endianness::Little), Ok(bytes));
+        assert_eq!(Kind::Guid.encode("0001", Endianness::Big), Err(ValueError::Invalid));
+    }
+}
@@ -0,0 +1,31 @@
+//! Data model for an [`Hexview`].
+//!
+//! [`Hexview`]: ../../native/hexview/struct.Hexview.html
+
+pub mod diff;
+pub mod edit;
+pub mod encoding;
+pub mod expr;
+pub mod file;
+pub mod history;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+pub mod piece_table;
+pub mod search;
+pub mod selection;
+pub mod source;
+pub mod template;
+
+pub use edit::Edit;
+pub use encoding::{Encoding, Glyph};
+pub use expr::{parse_offset, ExprError};
+pub use file::FileSource;
+pub use history::History;
+pub use piece_table::PieceTable;
+pub use search::{
+    find_all, find_next, find_regex, ByteRegex, Cancelled, Pattern, PatternError, RegexError,
+    RegexMatch, ReplaceError, ReplaceMode, DEFAULT_MAX_MATCH_LEN,
+};
+pub use selection::Selection;
+pub use source::ByteSource;
+pub use template::{Endianness, Field, Template, TemplateError};
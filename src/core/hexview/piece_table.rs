@@ -0,0 +1,425 @@
+//! An editable [`ByteSource`] that never modifies the source it wraps.
+//!
+//! [`ByteSource`]: ../source/trait.ByteSource.html
+
+use std::{io, ops::Range};
+use super::source::{read_slice, ByteSource};
+
+/// An editable [`ByteSource`] built on top of another one.
+///
+/// The data is described by a list of pieces, each one pointing either to a
+/// range of the original source or to a range of an append-only buffer with
+/// every byte that was added. Edits only ever split and shuffle pieces around,
+/// so inserting at the start of a huge source is as cheap as anywhere else and
+/// the original source is never written to.
+///
+/// [`ByteSource`]: ../source/trait.ByteSource.html
+#[derive(Debug)]
+pub struct PieceTable {
+    original: Box<dyn ByteSource>,
+    added: Vec<u8>,
+    pieces: Vec<Piece>,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Buffer {
+    Original,
+    Added,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    buffer: Buffer,
+    start: usize,
+    len: usize,
+}
+
+impl PieceTable {
+    /// Creates a new [`PieceTable`] with the contents of `original`.
+    ///
+    /// [`PieceTable`]: struct.PieceTable.html
+    pub fn new(original: impl ByteSource + 'static) -> Self {
+        let len = original.len();
+        let pieces = if len > 0 {
+            vec![Piece { buffer: Buffer::Original, start: 0, len }]
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            original: Box::new(original),
+            added: Vec::new(),
+            pieces,
+            len,
+        }
+    }
+
+    /// Returns the source the [`PieceTable`] was created from.
+    ///
+    /// [`PieceTable`]: struct.PieceTable.html
+    pub fn original(&self) -> &dyn ByteSource {
+        &*self.original
+    }
+
+    /// Returns whether the data differs in any way from the original source.
+    pub fn is_modified(&self) -> bool {
+        match self.pieces.as_slice() {
+            [] => self.original.len() != 0,
+            [piece] => piece.buffer != Buffer::Original || piece.len != self.original.len(),
+            _ => true,
+        }
+    }
+
+    /// Inserts `bytes` before `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is greater than the length.
+    pub fn insert(&mut self, offset: usize, bytes: &[u8]) {
+        assert!(offset <= self.len, "insert offset out of bounds");
+
+        if bytes.is_empty() {
+            return;
+        }
+
+        let index = self.split(offset);
+        let start = self.added.len();
+        self.added.extend_from_slice(bytes);
+        self.len += bytes.len();
+
+        // Typing extends the previous piece instead of creating a new one
+        if index > 0 {
+            let previous = &mut self.pieces[index - 1];
+
+            if previous.buffer == Buffer::Added && previous.start + previous.len == start {
+                previous.len += bytes.len();
+                return;
+            }
+        }
+
+        self.pieces.insert(index, Piece {
+            buffer: Buffer::Added,
+            start,
+            len: bytes.len(),
+        });
+    }
+
+    /// Removes the bytes in `range`, returning them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` goes past the length.
+    pub fn delete(&mut self, range: Range<usize>) -> Vec<u8> {
+        assert!(range.start <= range.end && range.end <= self.len, "delete range out of bounds");
+
+        if range.start == range.end {
+            return Vec::new();
+        }
+
+        let mut removed = vec![0; range.end - range.start];
+        self.read(range.start, &mut removed);
+
+        let first = self.split(range.start);
+        let last = self.split(range.end);
+        self.pieces.drain(first..last);
+        self.len -= removed.len();
+
+        removed
+    }
+
+    /// Replaces the bytes in `range` with `bytes`, returning the replaced bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` goes past the length.
+    pub fn replace(&mut self, range: Range<usize>, bytes: &[u8]) -> Vec<u8> {
+        let offset = range.start;
+        let removed = self.delete(range);
+        self.insert(offset, bytes);
+
+        removed
+    }
+
+    /// Replaces the bytes in every one of `ranges` with `bytes`, returning
+    /// the replaced bytes of each one.
+    ///
+    /// The ranges are relative to the data before any of them is replaced.
+    /// The pieces are rebuilt once for all of them and every replacement
+    /// shares the same added bytes, so this is much cheaper than calling
+    /// [`replace`] for each range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ranges aren't sorted, overlap or go past the length.
+    ///
+    /// [`replace`]: #method.replace
+    pub fn replace_ranges(&mut self, ranges: &[Range<usize>], bytes: &[u8]) -> Vec<Vec<u8>> {
+        let mut last_end = 0;
+
+        for range in ranges {
+            assert!(
+                last_end <= range.start && range.start <= range.end && range.end <= self.len,
+                "replace ranges out of order or out of bounds",
+            );
+            last_end = range.end;
+        }
+
+        let added = Piece {
+            buffer: Buffer::Added,
+            start: self.added.len(),
+            len: bytes.len(),
+        };
+        self.added.extend_from_slice(bytes);
+
+        let old_pieces = std::mem::take(&mut self.pieces);
+        let mut pieces = Vec::with_capacity(old_pieces.len() + ranges.len() * 2);
+        let mut removed: Vec<Vec<u8>> = ranges.iter().map(|range| Vec::with_capacity(range.len())).collect();
+        let mut index = 0;
+        let mut piece_start = 0;
+
+        for piece in old_pieces {
+            let piece_end = piece_start + piece.len;
+            let mut position = piece_start;
+
+            while position < piece_end {
+                match ranges.get(index) {
+                    // Inside of a range, which gets the new bytes where it starts
+                    Some(range) if range.start <= position => {
+                        if range.start == position && added.len > 0 {
+                            pieces.push(added);
+                        }
+
+                        let end = range.end.min(piece_end);
+                        let old = &mut removed[index];
+                        let old_len = old.len();
+
+                        old.resize(old_len + end - position, 0);
+                        self.read_piece(piece, position - piece_start, &mut old[old_len..]);
+
+                        if end == range.end {
+                            index += 1;
+                        }
+
+                        position = end;
+                    }
+                    // Between ranges, which is kept as it was
+                    next => {
+                        let end = next.map_or(piece_end, |range| range.start.min(piece_end));
+
+                        pieces.push(Piece {
+                            buffer: piece.buffer,
+                            start: piece.start + (position - piece_start),
+                            len: end - position,
+                        });
+
+                        position = end;
+                    }
+                }
+            }
+
+            piece_start = piece_end;
+        }
+
+        // Only empty ranges can be left, right at the end
+        if added.len > 0 {
+            for _ in index..ranges.len() {
+                pieces.push(added);
+            }
+        }
+
+        let removed_len: usize = removed.iter().map(Vec::len).sum();
+
+        self.pieces = pieces;
+        self.len = self.len - removed_len + ranges.len() * bytes.len();
+
+        removed
+    }
+
+    /// Reads the bytes of `piece` from `skip` on, filling `buf`.
+    fn read_piece(&self, piece: Piece, skip: usize, buf: &mut [u8]) {
+        let start = piece.start + skip;
+
+        match piece.buffer {
+            Buffer::Original => {
+                self.original.read(start, buf);
+            }
+            Buffer::Added => buf.copy_from_slice(&self.added[start..(start + buf.len())]),
+        }
+    }
+
+    /// Makes sure a piece starts at `offset`, returning its index.
+    ///
+    /// If `offset` is the length, the amount of pieces is returned.
+    fn split(&mut self, offset: usize) -> usize {
+        let mut piece_start = 0;
+
+        for index in 0..self.pieces.len() {
+            let piece = self.pieces[index];
+
+            if offset == piece_start {
+                return index;
+            }
+
+            if offset < piece_start + piece.len {
+                let head_len = offset - piece_start;
+
+                self.pieces[index].len = head_len;
+                self.pieces.insert(index + 1, Piece {
+                    buffer: piece.buffer,
+                    start: piece.start + head_len,
+                    len: piece.len - head_len,
+                });
+
+                return index + 1;
+            }
+
+            piece_start += piece.len;
+        }
+
+        self.pieces.len()
+    }
+}
+
+impl ByteSource for PieceTable {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let mut piece_start = 0;
+        let mut count = 0;
+
+        for piece in &self.pieces {
+            if count == buf.len() {
+                break;
+            }
+
+            let piece_end = piece_start + piece.len;
+            let position = offset + count;
+
+            if position < piece_end {
+                let skip = position - piece_start;
+                let buf = &mut buf[count..];
+                let wanted = buf.len().min(piece.len - skip);
+                let start = piece.start + skip;
+
+                count += match piece.buffer {
+                    Buffer::Original => self.original.read(start, &mut buf[..wanted]),
+                    Buffer::Added => read_slice(&self.added[..start + wanted], start, buf),
+                };
+            }
+
+            piece_start = piece_end;
+        }
+
+        count
+    }
+
+    fn is_writable(&self) -> bool {
+        true
+    }
+
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> io::Result<()> {
+        let end = offset.checked_add(bytes.len()).filter(|end| *end <= self.len);
+
+        match end {
+            Some(end) => {
+                self.replace(offset..end, bytes);
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "write past the end of the byte source",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ByteSource, PieceTable};
+
+    fn contents(table: &PieceTable) -> Vec<u8> {
+        let mut buf = vec![0; table.len()];
+        assert_eq!(table.read(0, &mut buf), table.len());
+        buf
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut table = PieceTable::new(b"world".to_vec());
+
+        table.insert(0, b"hello ");
+        table.insert(11, b"!");
+        table.insert(5, b",");
+
+        assert_eq!(contents(&table), b"hello, world!");
+        assert!(table.is_modified());
+    }
+
+    #[test]
+    fn test_typing_reuses_pieces() {
+        let mut table = PieceTable::new(Vec::new());
+
+        for (i, b) in b"abc".iter().enumerate() {
+            table.insert(i, &[*b]);
+        }
+
+        assert_eq!(contents(&table), b"abc");
+        assert_eq!(table.pieces.len(), 1);
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut table = PieceTable::new(b"0123456789".to_vec());
+
+        table.insert(5, b"abc");
+        assert_eq!(table.delete(3..9), b"34abc5");
+        assert_eq!(contents(&table), b"0126789");
+        assert_eq!(table.delete(0..7), b"0126789");
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_replace_ranges() {
+        let mut table = PieceTable::new(b"0123456789".to_vec());
+
+        table.insert(5, b"abc");
+        let removed = table.replace_ranges(&[0..0, 1..3, 4..7, 13..13], b"xy");
+
+        assert_eq!(removed, vec![b"".to_vec(), b"12".to_vec(), b"4ab".to_vec(), b"".to_vec()]);
+        assert_eq!(contents(&table), b"xy0xy3xyc56789xy");
+        assert_eq!(table.len(), 16);
+
+        let removed = table.replace_ranges(&[0..2, 14..16], b"");
+
+        assert_eq!(removed, vec![b"xy".to_vec(), b"xy".to_vec()]);
+        assert_eq!(contents(&table), b"0xy3xyc56789");
+    }
+
+    #[test]
+    fn test_write() {
+        let mut table = PieceTable::new(b"0123".to_vec());
+
+        table.write(1, b"ab").unwrap();
+        assert_eq!(contents(&table), b"0ab3");
+        assert!(table.write(3, b"ab").is_err());
+
+        // The original source is left as it was
+        let mut original = [0; 4];
+        table.original().read(0, &mut original);
+        assert_eq!(&original, b"0123");
+    }
+
+    #[test]
+    fn test_partial_read() {
+        let mut table = PieceTable::new(b"0123".to_vec());
+        let mut buf = [0; 4];
+
+        table.insert(2, b"ab");
+        assert_eq!(table.read(3, &mut buf), 3);
+        assert_eq!(&buf[..3], b"b23");
+        assert_eq!(table.read(6, &mut buf), 0);
+    }
+}
@@ -0,0 +1,539 @@
+//! Searching for patterns in the data of an [`Hexview`].
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+use std::{
+    error::Error,
+    fmt,
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use regex::bytes::{Regex, RegexBuilder};
+use super::ByteSource;
+
+/// How much data is read at once while searching.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The default limit for the length of [`ByteRegex`] matches, see
+/// [`find_regex`].
+///
+/// [`ByteRegex`]: struct.ByteRegex.html
+///
+/// [`find_regex`]: fn.find_regex.html
+pub const DEFAULT_MAX_MATCH_LEN: usize = 4096;
+
+/// A sequence of bytes to search for.
+///
+/// Patterns can be made from hex digits with wildcards, or from text, which
+/// may ignore the case of ASCII letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Element {
+    /// Matches a single byte.
+    Byte(u8),
+    /// Matches an ASCII letter in either case, stored in lowercase.
+    Caseless(u8),
+    /// Matches anything.
+    Any,
+}
+
+/// An error found while parsing a hex [`Pattern`].
+///
+/// [`Pattern`]: struct.Pattern.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// There was nothing to search for.
+    Empty,
+    /// A character that isn't a hex digit or a wildcard was found at the
+    /// given byte index.
+    InvalidDigit(usize, char),
+    /// The last byte is missing its second digit.
+    OddDigits,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "empty pattern"),
+            PatternError::InvalidDigit(index, c) => {
+                write!(f, "invalid hex digit `{}` at position {}", c, index)
+            }
+            PatternError::OddDigits => write!(f, "odd number of hex digits"),
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+impl Pattern {
+    /// Parses a [`Pattern`] from pairs of hex digits, where `??` matches any
+    /// byte.
+    ///
+    /// Whitespace between pairs is ignored, so both `DEADBEEF` and
+    /// `DE AD ?? EF` are valid.
+    ///
+    /// [`Pattern`]: struct.Pattern.html
+    pub fn hex(input: &str) -> Result<Self, PatternError> {
+        let mut elements = Vec::new();
+        let mut high = None;
+
+        for (index, c) in input.char_indices() {
+            if c.is_whitespace() {
+                continue;
+            }
+
+            let digit = match c {
+                '?' => None,
+                _ => Some(c.to_digit(16).ok_or(PatternError::InvalidDigit(index, c))? as u8),
+            };
+
+            high = match high {
+                None => Some(digit),
+                Some(high) => {
+                    elements.push(match (high, digit) {
+                        (Some(high), Some(low)) => Element::Byte((high << 4) | low),
+                        (None, None) => Element::Any,
+                        // Half wildcards aren't supported
+                        _ => return Err(PatternError::InvalidDigit(index, c)),
+                    });
+
+                    None
+                }
+            };
+        }
+
+        if high.is_some() {
+            Err(PatternError::OddDigits)
+        } else if elements.is_empty() {
+            Err(PatternError::Empty)
+        } else {
+            Ok(Self { elements })
+        }
+    }
+
+    /// Creates a [`Pattern`] matching `text` encoded as UTF-8, which is plain
+    /// ASCII for ASCII text.
+    ///
+    /// [`Pattern`]: struct.Pattern.html
+    pub fn text(text: &str, case_sensitive: bool) -> Self {
+        Self {
+            elements: text.bytes().map(|b| text_element(b, case_sensitive)).collect(),
+        }
+    }
+
+    /// Creates a [`Pattern`] matching `text` encoded as UTF-16LE.
+    ///
+    /// [`Pattern`]: struct.Pattern.html
+    pub fn utf16le(text: &str, case_sensitive: bool) -> Self {
+        let elements = text.encode_utf16().flat_map(|unit| {
+            let [low, high] = unit.to_le_bytes();
+
+            // Only ASCII letters have a case to ignore
+            let low = if high == 0 {
+                text_element(low, case_sensitive)
+            } else {
+                Element::Byte(low)
+            };
+
+            vec![low, Element::Byte(high)]
+        }).collect();
+
+        Self { elements }
+    }
+
+    /// Returns the amount of bytes matched by the [`Pattern`].
+    ///
+    /// [`Pattern`]: struct.Pattern.html
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether the [`Pattern`] matches nothing at all.
+    ///
+    /// [`Pattern`]: struct.Pattern.html
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns whether `bytes` is a match, and nothing more.
+    pub fn is_match(&self, bytes: &[u8]) -> bool {
+        bytes.len() == self.elements.len() && self.matches(bytes)
+    }
+
+    /// Returns whether `bytes` starts with a match.
+    fn matches(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= self.elements.len()
+            && self.elements.iter().zip(bytes).all(|(element, b)| match element {
+                Element::Byte(expected) => b == expected,
+                Element::Caseless(expected) => b.to_ascii_lowercase() == *expected,
+                Element::Any => true,
+            })
+    }
+}
+
+fn text_element(b: u8, case_sensitive: bool) -> Element {
+    if !case_sensitive && b.is_ascii_alphabetic() {
+        Element::Caseless(b.to_ascii_lowercase())
+    } else {
+        Element::Byte(b)
+    }
+}
+
+/// Finds every match of `pattern` in `source`, in order.
+///
+/// Matches don't overlap, and the data is read in chunks, so `source` can be
+/// as big as it needs to be.
+pub fn find_all(source: &dyn ByteSource, pattern: &Pattern) -> Vec<Range<usize>> {
+    find(source, pattern, 0, usize::MAX)
+}
+
+/// Finds the first match of `pattern` in `source` starting at `from` or
+/// after it.
+pub fn find_next(source: &dyn ByteSource, pattern: &Pattern, from: usize) -> Option<Range<usize>> {
+    find(source, pattern, from, 1).pop()
+}
+
+/// Finds up to `limit` matches of `pattern` starting at `from` or after it.
+fn find(
+    source: &dyn ByteSource,
+    pattern: &Pattern,
+    from: usize,
+    limit: usize,
+) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    let pattern_len = pattern.len();
+
+    if pattern_len == 0 {
+        return matches;
+    }
+
+    // Each chunk keeps the end of the previous one, so matches crossing
+    // chunks are still found
+    let mut buffer = vec![0; CHUNK_SIZE.max(pattern_len * 2)];
+    let mut chunk_start = from;
+    let mut next = from;
+
+    loop {
+        let count = source.read(chunk_start, &mut buffer);
+        let chunk = &buffer[..count];
+
+        if count < pattern_len {
+            break;
+        }
+
+        let mut i = next - chunk_start;
+
+        while i + pattern_len <= count {
+            if pattern.matches(&chunk[i..]) {
+                matches.push((chunk_start + i)..(chunk_start + i + pattern_len));
+                i += pattern_len;
+
+                if matches.len() == limit {
+                    return matches;
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        next = chunk_start + i;
+
+        if count < buffer.len() {
+            break;
+        }
+
+        chunk_start += count - (pattern_len - 1);
+        next = next.max(chunk_start);
+    }
+
+    matches
+}
+
+/// How a replacement with a different length than what it replaces is
+/// handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceMode {
+    /// Replacements must be as long as the pattern, so they overwrite the
+    /// data in place.
+    Overwrite,
+    /// Replacements can have any length, shifting the data after them.
+    Resize,
+}
+
+/// An error found while replacing matches of a [`Pattern`].
+///
+/// [`Pattern`]: struct.Pattern.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceError {
+    /// The replacement isn't as long as the pattern, which is required by
+    /// [`ReplaceMode::Overwrite`].
+    ///
+    /// [`ReplaceMode::Overwrite`]: enum.ReplaceMode.html#variant.Overwrite
+    LengthMismatch {
+        /// The length of the pattern.
+        pattern: usize,
+        /// The length of the replacement.
+        replacement: usize,
+    },
+}
+
+impl fmt::Display for ReplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplaceError::LengthMismatch { pattern, replacement } => write!(
+                f,
+                "replacement is {} bytes long, but the pattern is {} bytes long",
+                replacement, pattern,
+            ),
+        }
+    }
+}
+
+impl Error for ReplaceError {}
+
+impl ReplaceMode {
+    /// Checks whether `replacement` can replace matches of `pattern`.
+    pub fn check(self, pattern: &Pattern, replacement: &[u8]) -> Result<(), ReplaceError> {
+        match self {
+            ReplaceMode::Overwrite if pattern.len() != replacement.len() => {
+                Err(ReplaceError::LengthMismatch {
+                    pattern: pattern.len(),
+                    replacement: replacement.len(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A regular expression that matches raw bytes.
+///
+/// Unicode mode is off, so escapes like `\x89` and `.` match single bytes
+/// instead of UTF-8 encoded characters, and `.` matches line feeds too. A
+/// pattern like `\x89PNG.{4}IHDR` would never match in binary data
+/// otherwise.
+#[derive(Debug, Clone)]
+pub struct ByteRegex(Regex);
+
+/// An error found while parsing a [`ByteRegex`].
+///
+/// [`ByteRegex`]: struct.ByteRegex.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegexError(regex::Error);
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for RegexError {}
+
+impl ByteRegex {
+    /// Parses a [`ByteRegex`] from `pattern`, with the usual regex syntax.
+    ///
+    /// [`ByteRegex`]: struct.ByteRegex.html
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        RegexBuilder::new(pattern)
+            .unicode(false)
+            .dot_matches_new_line(true)
+            .build()
+            .map(ByteRegex)
+            .map_err(RegexError)
+    }
+
+    /// Returns the pattern the [`ByteRegex`] was parsed from.
+    ///
+    /// [`ByteRegex`]: struct.ByteRegex.html
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+/// A match of a [`ByteRegex`].
+///
+/// [`ByteRegex`]: struct.ByteRegex.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexMatch {
+    /// The range of the whole match.
+    pub range: Range<usize>,
+    /// The range of each capture group, starting with the first one. Groups
+    /// that didn't participate in the match are `None`.
+    pub groups: Vec<Option<Range<usize>>>,
+}
+
+/// The error returned by [`find_regex`] when it's cancelled.
+///
+/// [`find_regex`]: fn.find_regex.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "search cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Finds every match of `regex` in `source`, in order.
+///
+/// The data is read in chunks, so `source` can be as big as it needs to be,
+/// but matches can't be longer than `max_match_len`. Anchors and word
+/// boundaries may also match where chunks start and end.
+///
+/// Searching a big file can take a while, so it can be run in another thread
+/// with its own [`FileSource`] and cancelled by setting `cancel`, which is
+/// checked after every chunk.
+///
+/// [`FileSource`]: ../file/struct.FileSource.html
+pub fn find_regex(
+    source: &dyn ByteSource,
+    regex: &ByteRegex,
+    max_match_len: usize,
+    cancel: Option<&AtomicBool>,
+) -> Result<Vec<RegexMatch>, Cancelled> {
+    let mut matches = Vec::new();
+
+    // Each window goes past its chunk, so matches starting in the chunk are
+    // complete. The ones starting past it are found again in the next one.
+    let mut buffer = vec![0; CHUNK_SIZE + max_match_len];
+    let mut window_start = 0;
+
+    loop {
+        if cancel.map_or(false, |cancel| cancel.load(Ordering::Relaxed)) {
+            return Err(Cancelled);
+        }
+
+        let count = source.read(window_start, &mut buffer);
+        let window = &buffer[..count];
+        let is_last = count < buffer.len();
+        let mut next = window_start + CHUNK_SIZE;
+
+        for captures in regex.0.captures_iter(window) {
+            let whole = captures.get(0).unwrap();
+
+            if !is_last && whole.start() >= CHUNK_SIZE {
+                break;
+            }
+
+            let to_range = |m: regex::bytes::Match<'_>| {
+                (window_start + m.start())..(window_start + m.end())
+            };
+
+            next = next.max(window_start + whole.end());
+            matches.push(RegexMatch {
+                range: to_range(whole),
+                groups: captures.iter().skip(1).map(|group| group.map(to_range)).collect(),
+            });
+        }
+
+        if is_last {
+            return Ok(matches);
+        }
+
+        window_start = next;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::AtomicBool;
+    use super::{find_all, find_next, find_regex, ByteRegex, Cancelled, Pattern, PatternError};
+
+    #[test]
+    fn test_hex() {
+        let data = b"\x00\xDE\xAD\xBE\xEF\xDE\x00\xBE\xEF".to_vec();
+
+        assert_eq!(find_all(&data, &Pattern::hex("DEADBEEF").unwrap()), vec![1..5]);
+        assert_eq!(find_all(&data, &Pattern::hex("de ?? be ef").unwrap()), vec![1..5, 5..9]);
+        assert_eq!(Pattern::hex("DE A"), Err(PatternError::OddDigits));
+        assert_eq!(Pattern::hex("D?"), Err(PatternError::InvalidDigit(1, '?')));
+        assert_eq!(Pattern::hex(" "), Err(PatternError::Empty));
+    }
+
+    #[test]
+    fn test_find_next() {
+        let data = b"abcabcabc".to_vec();
+        let pattern = Pattern::text("abc", true);
+
+        assert_eq!(find_next(&data, &pattern, 1), Some(3..6));
+        assert_eq!(find_next(&data, &pattern, 6), Some(6..9));
+        assert_eq!(find_next(&data, &pattern, 7), None);
+    }
+
+    #[test]
+    fn test_text() {
+        let data = b"Hello, hello, HELLO".to_vec();
+
+        assert_eq!(find_all(&data, &Pattern::text("hello", true)), vec![7..12]);
+        assert_eq!(find_all(&data, &Pattern::text("hello", false)).len(), 3);
+    }
+
+    #[test]
+    fn test_utf16le() {
+        let data = b"\x00H\x00i\x00!\x00".to_vec();
+
+        assert_eq!(find_all(&data, &Pattern::utf16le("hi", false)), vec![1..5]);
+        assert!(find_all(&data, &Pattern::utf16le("hi", true)).is_empty());
+    }
+
+    #[test]
+    fn test_across_chunks() {
+        let mut data = vec![0; super::CHUNK_SIZE * 2 + 1];
+        let offsets = [0, super::CHUNK_SIZE - 2, super::CHUNK_SIZE * 2 - 3];
+
+        for offset in &offsets {
+            data[*offset..(*offset + 4)].copy_from_slice(b"ABCD");
+        }
+
+        let matches: Vec<usize> = find_all(&data, &Pattern::text("ABCD", true))
+            .into_iter()
+            .map(|range| range.start)
+            .collect();
+
+        assert_eq!(matches, offsets);
+    }
+
+    #[test]
+    fn test_regex() {
+        let mut data = vec![0; super::CHUNK_SIZE * 2];
+        let offsets = [2, super::CHUNK_SIZE - 6, super::CHUNK_SIZE + 60];
+
+        for offset in &offsets {
+            data[*offset..(*offset + 16)].copy_from_slice(b"\x89PNG\0\0\0\rIHDR\0\0\0\x01");
+        }
+
+        let regex = ByteRegex::new(r"\x89PNG.{4}(IHDR)(x)?").unwrap();
+        let matches = find_regex(&data, &regex, 64, None).unwrap();
+
+        let starts: Vec<usize> = matches.iter().map(|m| m.range.start).collect();
+
+        assert_eq!(starts, offsets);
+        assert_eq!(matches[0].range, 2..14);
+        assert_eq!(matches[0].groups, vec![Some(10..14), None]);
+    }
+
+    #[test]
+    fn test_byte_regex() {
+        let data = b"\0\x89PNG\r\n\x1a\nIHDR\xff".to_vec();
+        let regex = ByteRegex::new(r"\x89PNG.{4}IHDR\xFF").unwrap();
+        let matches = find_regex(&data, &regex, 64, None).unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].range, 1..14);
+        assert!(ByteRegex::new("(").is_err());
+    }
+
+    #[test]
+    fn test_regex_cancel() {
+        let regex = ByteRegex::new("a").unwrap();
+        let cancel = AtomicBool::new(true);
+
+        assert_eq!(find_regex(&b"aaa".to_vec(), &regex, 64, Some(&cancel)), Err(Cancelled));
+    }
+}
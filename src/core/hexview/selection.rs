@@ -0,0 +1,58 @@
+//! Selections made in an [`Hexview`].
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+use std::ops::Range;
+
+/// A range of selected bytes in an [`Hexview`].
+///
+/// A selection starts at its `anchor` and grows or shrinks by moving its
+/// `active` end, which can be on either side of the anchor. The bytes between
+/// both ends are selected, not including the one at the greatest of them.
+///
+/// [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    /// Where the selection was started.
+    pub anchor: usize,
+    /// The end of the selection that moves.
+    pub active: usize,
+}
+
+impl Selection {
+    /// Creates a new [`Selection`] from `anchor` to `active`.
+    ///
+    /// [`Selection`]: struct.Selection.html
+    pub fn new(anchor: usize, active: usize) -> Self {
+        Self { anchor, active }
+    }
+
+    /// Returns the range of selected bytes.
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.active)..self.anchor.max(self.active)
+    }
+
+    /// Returns the amount of selected bytes.
+    pub fn len(&self) -> usize {
+        self.anchor.max(self.active) - self.anchor.min(self.active)
+    }
+
+    /// Returns whether no bytes are selected, which is when both ends are the
+    /// same.
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.active
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Selection;
+
+    #[test]
+    fn test_range() {
+        assert_eq!(Selection::new(2, 6).range(), 2..6);
+        assert_eq!(Selection::new(6, 2).range(), 2..6);
+        assert_eq!(Selection::new(6, 2).len(), 4);
+        assert!(Selection::new(3, 3).is_empty());
+    }
+}
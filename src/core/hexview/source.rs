@@ -0,0 +1,146 @@
+//! Sources of bytes for an [`Hexview`].
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+
+use std::{borrow::Cow, fmt, io, sync::Arc};
+
+/// A source of bytes an [`Hexview`] can display.
+///
+/// The widget never asks for the whole data at once, only for the ranges it is about to draw, so
+/// implementors are free to keep their contents somewhere other than memory.
+///
+/// Sources must be [`Send`] so the widget state can be moved to another thread, e.g. to run a
+/// search in the background.
+///
+/// [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+/// [`Send`]: https://doc.rust-lang.org/std/marker/trait.Send.html
+pub trait ByteSource: fmt::Debug + Send {
+    /// Returns the amount of bytes in the source.
+    fn len(&self) -> usize;
+
+    /// Returns whether the source has no bytes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads bytes starting at `offset` into `buf`, returning how many bytes were read.
+    ///
+    /// Less bytes than `buf.len()` are read only when the end of the source is reached.
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize;
+
+    /// Returns whether [`write`] is supported by this source.
+    ///
+    /// [`write`]: #method.write
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    /// Overwrites bytes starting at `offset` with `bytes`.
+    ///
+    /// The default implementation fails, meaning the source is read-only.
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> io::Result<()> {
+        let _ = (offset, bytes);
+        Err(read_only())
+    }
+}
+
+impl ByteSource for Vec<u8> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize {
+        read_slice(self, offset, buf)
+    }
+
+    fn is_writable(&self) -> bool {
+        true
+    }
+
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> io::Result<()> {
+        write_slice(self, offset, bytes)
+    }
+}
+
+impl ByteSource for &'static [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize {
+        read_slice(self, offset, buf)
+    }
+}
+
+impl ByteSource for Arc<[u8]> {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize {
+        read_slice(self, offset, buf)
+    }
+}
+
+impl ByteSource for Cow<'static, [u8]> {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> usize {
+        read_slice(self, offset, buf)
+    }
+}
+
+/// Copies bytes from `slice` starting at `offset` into `buf`, returning how many were copied.
+pub(crate) fn read_slice(slice: &[u8], offset: usize, buf: &mut [u8]) -> usize {
+    if offset >= slice.len() {
+        return 0;
+    }
+
+    let count = buf.len().min(slice.len() - offset);
+    buf[..count].copy_from_slice(&slice[offset..offset + count]);
+    count
+}
+
+/// Overwrites bytes in `slice` starting at `offset`, failing if they don't fit.
+fn write_slice(slice: &mut [u8], offset: usize, bytes: &[u8]) -> io::Result<()> {
+    let end = offset.checked_add(bytes.len()).filter(|end| *end <= slice.len());
+
+    match end {
+        Some(end) => {
+            slice[offset..end].copy_from_slice(bytes);
+            Ok(())
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "write past the end of the byte source",
+        )),
+    }
+}
+
+fn read_only() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "byte source is read-only")
+}
+
+#[cfg(test)]
+mod test {
+    use super::ByteSource;
+    use std::{borrow::Cow, sync::Arc};
+
+    #[test]
+    fn test_shared_sources() {
+        let shared: Arc<[u8]> = Arc::from(&b"abcd"[..]);
+        let borrowed: Cow<'static, [u8]> = Cow::Borrowed(b"abcd");
+        let mut buf = [0; 3];
+
+        assert_eq!(ByteSource::len(&shared), 4);
+        assert_eq!(shared.read(2, &mut buf), 2);
+        assert_eq!(&buf[..2], b"cd");
+
+        assert_eq!(ByteSource::len(&borrowed), 4);
+        assert_eq!(borrowed.read(1, &mut buf), 3);
+        assert_eq!(&buf, b"bcd");
+        assert!(!borrowed.is_writable());
+    }
+}
@@ -0,0 +1,943 @@
+//! Templates describing binary structures, which turn the bytes of an
+//! [`Hexview`] into a tree of named fields.
+//!
+//! A [`Template`] can be built in code:
+//!
+//! ```
+//! use iced_widgets::core::hexview::template::{Count, Endianness, Enum, Struct, Template, Type};
+//!
+//! let template = Template::new("Header")
+//!     .endianness(Endianness::Big)
+//!     .define_struct(
+//!         Struct::new("Header")
+//!             .field("magic", Type::array(Type::U8, Count::Fixed(4)))
+//!             .field("kind", Type::named("Kind"))
+//!             .field("count", Type::U16)
+//!             .field("entries", Type::array(Type::named("Entry"), Count::Field("count".into()))),
+//!     )
+//!     .define_struct(Struct::new("Entry").field("offset", Type::U32).field("size", Type::U32))
+//!     .define_enum(Enum::new("Kind", Type::U8).variant(1, "Image").variant(2, "Sound"));
+//! ```
+//!
+//! Or parsed from text, where the first struct is the root:
+//!
+//! ```text
+//! # Defaults to little endian
+//! endian big
+//!
+//! struct Header {
+//!     magic: [u8; 4]
+//!     kind: Kind
+//!     count: u16
+//!     entries: [Entry; count]
+//! }
+//!
+//! struct Entry little {
+//!     offset: u32
+//!     size: u32
+//! }
+//!
+//! enum Kind: u8 {
+//!     Image = 1
+//!     Sound = 2
+//! }
+//! ```
+//!
+//! [`Hexview`]: ../../../native/hexview/struct.Hexview.html
+//! [`Template`]: struct.Template.html
+
+use super::{expr::parse_integer, ByteSource};
+use std::{error::Error, fmt, ops::Range};
+
+/// How deep structures can be nested, which stops templates that contain
+/// themselves.
+const MAX_DEPTH: usize = 64;
+
+/// The most elements of an array that are allocated for before reading them.
+const MAX_CAPACITY: usize = 1024;
+
+/// The order of the bytes of a multi-byte value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endianness {
+    /// The least significant byte comes first.
+    Little,
+    /// The most significant byte comes first.
+    Big,
+}
+
+impl Default for Endianness {
+    fn default() -> Self {
+        Endianness::Little
+    }
+}
+
+/// The type of a field in a [`Template`].
+///
+/// [`Template`]: struct.Template.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    /// An unsigned 8-bit integer.
+    U8,
+    /// An unsigned 16-bit integer.
+    U16,
+    /// An unsigned 32-bit integer.
+    U32,
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A signed 8-bit integer.
+    I8,
+    /// A signed 16-bit integer.
+    I16,
+    /// A signed 32-bit integer.
+    I32,
+    /// A signed 64-bit integer.
+    I64,
+    /// A 32-bit floating point number.
+    F32,
+    /// A 64-bit floating point number.
+    F64,
+    /// A [`Struct`] or [`Enum`] defined in the same template.
+    ///
+    /// [`Struct`]: struct.Struct.html
+    /// [`Enum`]: struct.Enum.html
+    Named(String),
+    /// Elements of the same type, one after the other.
+    ///
+    /// Arrays of bytes are a single field, the rest have a field for each
+    /// element.
+    Array(Box<Type>, Count),
+}
+
+impl Type {
+    /// Creates a [`Type::Named`].
+    ///
+    /// [`Type::Named`]: enum.Type.html#variant.Named
+    pub fn named(name: impl Into<String>) -> Self {
+        Type::Named(name.into())
+    }
+
+    /// Creates a [`Type::Array`].
+    ///
+    /// [`Type::Array`]: enum.Type.html#variant.Array
+    pub fn array(element: Type, count: Count) -> Self {
+        Type::Array(Box::new(element), count)
+    }
+
+    fn primitive(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Type::U8,
+            "u16" => Type::U16,
+            "u32" => Type::U32,
+            "u64" => Type::U64,
+            "i8" => Type::I8,
+            "i16" => Type::I16,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "f32" => Type::F32,
+            "f64" => Type::F64,
+            _ => return None,
+        })
+    }
+
+    /// The size of a primitive type, or `None` for the rest.
+    fn size(&self) -> Option<usize> {
+        match self {
+            Type::U8 | Type::I8 => Some(1),
+            Type::U16 | Type::I16 => Some(2),
+            Type::U32 | Type::I32 | Type::F32 => Some(4),
+            Type::U64 | Type::I64 | Type::F64 => Some(8),
+            Type::Named(_) | Type::Array(..) => None,
+        }
+    }
+
+    /// Truncates `value` to the width of an integer type, sign-extending it
+    /// back to 64 bits if the type is signed, the way its values are read.
+    fn wrap(&self, value: u64) -> u64 {
+        let shift = 64 - self.size().unwrap_or(8) * 8;
+
+        match self {
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 => (((value << shift) as i64) >> shift) as u64,
+            _ => (value << shift) >> shift,
+        }
+    }
+
+    /// Decodes a primitive type from `bytes`, which must have its size.
+    fn decode(&self, bytes: &[u8], endianness: Endianness) -> Value {
+        let mut buffer = [0; 8];
+
+        match endianness {
+            Endianness::Little => buffer[..bytes.len()].copy_from_slice(bytes),
+            Endianness::Big => {
+                for (target, byte) in buffer.iter_mut().zip(bytes.iter().rev()) {
+                    *target = *byte;
+                }
+            }
+        }
+
+        let unsigned = u64::from_le_bytes(buffer);
+        let shift = 64 - bytes.len() * 8;
+
+        match self {
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 => Value::Unsigned(unsigned),
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 => {
+                Value::Signed(((unsigned << shift) as i64) >> shift)
+            }
+            Type::F32 => Value::Float(f32::from_bits(unsigned as u32) as f64),
+            Type::F64 => Value::Float(f64::from_bits(unsigned)),
+            Type::Named(_) | Type::Array(..) => unreachable!("not a primitive type"),
+        }
+    }
+}
+
+/// The amount of elements in an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Count {
+    /// Always the same amount.
+    Fixed(usize),
+    /// The value of an integer field that comes earlier in the same struct.
+    Field(String),
+}
+
+/// A structure in a [`Template`], made of named fields one after the other.
+///
+/// [`Template`]: struct.Template.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    name: String,
+    fields: Vec<(String, Type)>,
+    endianness: Option<Endianness>,
+}
+
+impl Struct {
+    /// Creates an empty [`Struct`] called `name`.
+    ///
+    /// [`Struct`]: struct.Struct.html
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields: Vec::new(),
+            endianness: None,
+        }
+    }
+
+    /// Adds a field at the end of the [`Struct`].
+    ///
+    /// [`Struct`]: struct.Struct.html
+    pub fn field(mut self, name: impl Into<String>, ty: Type) -> Self {
+        self.fields.push((name.into(), ty));
+        self
+    }
+
+    /// Sets the [`Endianness`] of the fields of the [`Struct`], instead of
+    /// the one of the [`Template`].
+    ///
+    /// [`Endianness`]: enum.Endianness.html
+    /// [`Struct`]: struct.Struct.html
+    /// [`Template`]: struct.Template.html
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = Some(endianness);
+        self
+    }
+}
+
+/// An integer in a [`Template`] whose values have names.
+///
+/// [`Template`]: struct.Template.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enum {
+    name: String,
+    repr: Type,
+    variants: Vec<(u64, String)>,
+}
+
+impl Enum {
+    /// Creates an [`Enum`] called `name`, stored as `repr`.
+    ///
+    /// # Panics
+    /// If `repr` isn't an integer type.
+    ///
+    /// [`Enum`]: struct.Enum.html
+    pub fn new(name: impl Into<String>, repr: Type) -> Self {
+        assert!(
+            repr.size().is_some() && repr != Type::F32 && repr != Type::F64,
+            "enums must be stored as integers",
+        );
+
+        Self {
+            name: name.into(),
+            repr,
+            variants: Vec::new(),
+        }
+    }
+
+    /// Names a value of the [`Enum`].
+    ///
+    /// The value is truncated to the width of the repr. With a signed repr
+    /// it's then sign-extended, so `-1i64 as u64` and `0xFF` are the same
+    /// value of an `i8`, and it's shown as such in [`Value::Variant`].
+    ///
+    /// [`Enum`]: struct.Enum.html
+    /// [`Value::Variant`]: enum.Value.html#variant.Variant
+    pub fn variant(mut self, value: u64, name: impl Into<String>) -> Self {
+        let value = self.repr.wrap(value);
+
+        self.variants.push((value, name.into()));
+        self
+    }
+}
+
+/// A description of a binary structure, applied to some data to get a tree of
+/// [`Field`]s.
+///
+/// [`Field`]: struct.Field.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    root: String,
+    endianness: Endianness,
+    structs: Vec<Struct>,
+    enums: Vec<Enum>,
+}
+
+impl Template {
+    /// Creates a [`Template`] whose fields start with the struct called
+    /// `root`.
+    ///
+    /// [`Template`]: struct.Template.html
+    pub fn new(root: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            endianness: Endianness::default(),
+            structs: Vec::new(),
+            enums: Vec::new(),
+        }
+    }
+
+    /// Sets the [`Endianness`] of the fields of the [`Template`]. It's little
+    /// endian by default.
+    ///
+    /// [`Endianness`]: enum.Endianness.html
+    /// [`Template`]: struct.Template.html
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Adds a [`Struct`] to the [`Template`].
+    ///
+    /// [`Struct`]: struct.Struct.html
+    /// [`Template`]: struct.Template.html
+    pub fn define_struct(mut self, definition: Struct) -> Self {
+        self.structs.push(definition);
+        self
+    }
+
+    /// Adds an [`Enum`] to the [`Template`].
+    ///
+    /// [`Enum`]: struct.Enum.html
+    /// [`Template`]: struct.Template.html
+    pub fn define_enum(mut self, definition: Enum) -> Self {
+        self.enums.push(definition);
+        self
+    }
+
+    /// Parses a [`Template`] from its text format, described in the
+    /// [module documentation].
+    ///
+    /// [`Template`]: struct.Template.html
+    /// [module documentation]: index.html
+    pub fn parse(text: &str) -> Result<Self, TemplateError> {
+        let mut template = Template::new("");
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.split('#').next().unwrap_or("").trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        while let Some((number, line)) = lines.next() {
+            let syntax = |message: &str| TemplateError::Syntax(number, message.into());
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                ["endian", endianness] => template.endianness = parse_endianness(endianness)
+                    .ok_or_else(|| syntax("expected `little` or `big`"))?,
+                ["struct", header @ ..] => {
+                    let (name, endianness) = match header {
+                        [name, "{"] => (*name, None),
+                        [name, endianness, "{"] => (
+                            *name,
+                            Some(parse_endianness(endianness)
+                                .ok_or_else(|| syntax("expected `little` or `big`"))?),
+                        ),
+                        _ => return Err(syntax("expected `struct Name [little|big] {`")),
+                    };
+                    let mut definition = Struct::new(name);
+                    definition.endianness = endianness;
+
+                    for (number, line) in block(&mut lines, number)? {
+                        let mut parts = line.splitn(2, ':');
+                        let name = parts.next().unwrap_or("").trim();
+                        let ty = parts.next().map(str::trim).unwrap_or("");
+
+                        if !is_identifier(name) || ty.is_empty() {
+                            return Err(TemplateError::Syntax(number, "expected `name: type`".into()));
+                        }
+
+                        definition = definition.field(name, parse_type(ty, number)?);
+                    }
+
+                    if template.root.is_empty() {
+                        template.root = definition.name.clone();
+                    }
+
+                    template.structs.push(definition);
+                }
+                ["enum", name, repr, "{"] if name.ends_with(':') => {
+                    let repr = Type::primitive(repr)
+                        .filter(|repr| *repr != Type::F32 && *repr != Type::F64)
+                        .ok_or_else(|| syntax("enums must be stored as integers"))?;
+                    let mut definition = Enum::new(name.trim_end_matches(':'), repr);
+
+                    for (number, line) in block(&mut lines, number)? {
+                        let mut parts = line.splitn(2, '=');
+                        let name = parts.next().unwrap_or("").trim();
+                        let value = parts
+                            .next()
+                            .and_then(|value| parse_integer(value.trim()))
+                            .filter(|value| *value >= i64::MIN as i128 && *value <= u64::MAX as i128)
+                            .ok_or_else(|| TemplateError::Syntax(number, "expected `Name = value`".into()))?;
+
+                        definition = definition.variant(value as u64, name);
+                    }
+
+                    template.enums.push(definition);
+                }
+                _ => return Err(syntax("expected `endian`, `struct` or `enum`")),
+            }
+        }
+
+        if template.root.is_empty() {
+            return Err(TemplateError::Syntax(0, "no struct was defined".into()));
+        }
+
+        Ok(template)
+    }
+
+    /// Reads the fields of the [`Template`] from `source`, starting at
+    /// `offset`.
+    ///
+    /// [`Template`]: struct.Template.html
+    pub fn apply(&self, source: &dyn ByteSource, offset: usize) -> Result<Field, TemplateError> {
+        let root = Type::Named(self.root.clone());
+
+        self.read(source, &root, self.root.clone(), self.root.clone(), offset, self.endianness, &[], 0)
+    }
+
+    /// The fewest bytes a field of type `ty` can take, when every array
+    /// whose count is read from the data is empty.
+    fn min_size(&self, ty: &Type, depth: usize) -> usize {
+        if depth > MAX_DEPTH {
+            return 0;
+        }
+
+        match ty {
+            Type::Named(name) => {
+                if let Some(definition) = self.structs.iter().find(|s| s.name == *name) {
+                    definition
+                        .fields
+                        .iter()
+                        .fold(0, |size, (_, ty)| size.saturating_add(self.min_size(ty, depth + 1)))
+                } else if let Some(definition) = self.enums.iter().find(|e| e.name == *name) {
+                    self.min_size(&definition.repr, depth + 1)
+                } else {
+                    0
+                }
+            }
+            Type::Array(element, Count::Fixed(count)) => {
+                self.min_size(element, depth + 1).saturating_mul(*count)
+            }
+            Type::Array(_, Count::Field(_)) => 0,
+            ty => ty.size().unwrap_or(0),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn read(
+        &self,
+        source: &dyn ByteSource,
+        ty: &Type,
+        name: String,
+        path: String,
+        offset: usize,
+        endianness: Endianness,
+        siblings: &[Field],
+        depth: usize,
+    ) -> Result<Field, TemplateError> {
+        if depth > MAX_DEPTH {
+            return Err(TemplateError::TooDeep(path));
+        }
+
+        let leaf = |value: Value, size: usize| -> Result<Field, TemplateError> {
+            let end = offset.checked_add(size).filter(|end| *end <= source.len());
+
+            match end {
+                Some(end) => Ok(Field {
+                    name: name.clone(),
+                    path: path.clone(),
+                    range: offset..end,
+                    value,
+                    children: Vec::new(),
+                }),
+                None => Err(TemplateError::OutOfBounds(path.clone())),
+            }
+        };
+
+        if let Some(size) = ty.size() {
+            let mut bytes = [0; 8];
+
+            if source.read(offset, &mut bytes[..size]) < size {
+                return Err(TemplateError::OutOfBounds(path));
+            }
+
+            return leaf(ty.decode(&bytes[..size], endianness), size);
+        }
+
+        match ty {
+            Type::Named(type_name) => {
+                if let Some(definition) = self.structs.iter().find(|s| s.name == *type_name) {
+                    let endianness = definition.endianness.unwrap_or(endianness);
+                    let mut children: Vec<Field> = Vec::with_capacity(definition.fields.len());
+                    let mut end = offset;
+
+                    for (field_name, field_type) in &definition.fields {
+                        let child = self.read(
+                            source,
+                            field_type,
+                            field_name.clone(),
+                            format!("{}.{}", path, field_name),
+                            end,
+                            endianness,
+                            &children,
+                            depth + 1,
+                        )?;
+
+                        end = child.range.end;
+                        children.push(child);
+                    }
+
+                    Ok(Field {
+                        name,
+                        path,
+                        range: offset..end,
+                        value: Value::Struct(type_name.clone()),
+                        children,
+                    })
+                } else if let Some(definition) = self.enums.iter().find(|e| e.name == *type_name) {
+                    let field = self.read(
+                        source,
+                        &definition.repr,
+                        name,
+                        path,
+                        offset,
+                        endianness,
+                        siblings,
+                        depth + 1,
+                    )?;
+                    let value = match field.value {
+                        Value::Unsigned(value) => value,
+                        Value::Signed(value) => value as u64,
+                        _ => unreachable!("enums are stored as integers"),
+                    };
+                    let variant = definition
+                        .variants
+                        .iter()
+                        .find(|(variant, _)| *variant == value)
+                        .map(|(_, name)| name.clone());
+
+                    Ok(Field {
+                        value: Value::Variant(value, variant),
+                        ..field
+                    })
+                } else {
+                    Err(TemplateError::UndefinedType(type_name.clone()))
+                }
+            }
+            Type::Array(element, count) => {
+                let count = match count {
+                    Count::Fixed(count) => *count,
+                    Count::Field(field_name) => {
+                        let field = siblings
+                            .iter()
+                            .find(|field| field.name == *field_name)
+                            .ok_or_else(|| TemplateError::UndefinedField(field_name.clone()))?;
+
+                        match field.value {
+                            Value::Unsigned(count) => count as usize,
+                            Value::Signed(count) if count >= 0 => count as usize,
+                            Value::Variant(count, _) => count as usize,
+                            _ => return Err(TemplateError::InvalidCount(field.path.clone())),
+                        }
+                    }
+                };
+
+                if let Type::U8 | Type::I8 = **element {
+                    return leaf(Value::Bytes(count), count);
+                }
+
+                // Counts that can't fit in the data are stopped early, taking
+                // every element as at least a byte
+                let min_len = count.saturating_mul(self.min_size(element, depth).max(1));
+
+                if offset.saturating_add(min_len) > source.len() {
+                    return Err(TemplateError::OutOfBounds(path));
+                }
+
+                let mut children = Vec::with_capacity(count.min(MAX_CAPACITY));
+                let mut end = offset;
+
+                for index in 0..count {
+                    let child = self.read(
+                        source,
+                        element,
+                        format!("[{}]", index),
+                        format!("{}[{}]", path, index),
+                        end,
+                        endianness,
+                        &[],
+                        depth + 1,
+                    )?;
+
+                    end = child.range.end;
+                    children.push(child);
+                }
+
+                Ok(Field {
+                    name,
+                    path,
+                    range: offset..end,
+                    value: Value::Array(count),
+                    children,
+                })
+            }
+            _ => unreachable!("primitive types were read above"),
+        }
+    }
+}
+
+/// A field read by a [`Template`].
+///
+/// [`Template`]: struct.Template.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// The name of the field, or its index if it's an element of an array.
+    pub name: String,
+    /// The names leading to the field from the root, like
+    /// `Header.entries[2].size`.
+    pub path: String,
+    /// The bytes of the field.
+    pub range: Range<usize>,
+    /// The value of the field.
+    pub value: Value,
+    /// The fields inside this one, if it's a struct or an array.
+    pub children: Vec<Field>,
+}
+
+impl Field {
+    /// Returns the innermost [`Field`] containing `offset`.
+    ///
+    /// [`Field`]: struct.Field.html
+    pub fn field_at(&self, offset: usize) -> Option<&Field> {
+        if !self.range.contains(&offset) {
+            return None;
+        }
+
+        // Children are in order and don't overlap
+        let index = self
+            .children
+            .binary_search_by(|child| {
+                if child.range.end <= offset {
+                    std::cmp::Ordering::Less
+                } else if child.range.start > offset {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .ok();
+
+        match index {
+            Some(index) => self.children[index].field_at(offset),
+            None => Some(self),
+        }
+    }
+
+    /// Returns the fields without children, in order.
+    pub fn leaves(&self) -> Vec<&Field> {
+        if self.children.is_empty() {
+            vec![self]
+        } else {
+            self.children.iter().flat_map(Field::leaves).collect()
+        }
+    }
+}
+
+/// The value of a [`Field`].
+///
+/// [`Field`]: struct.Field.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// An unsigned integer.
+    Unsigned(u64),
+    /// A signed integer.
+    Signed(i64),
+    /// A floating point number.
+    Float(f64),
+    /// The value of an enum, with the name of its variant if it has one.
+    Variant(u64, Option<String>),
+    /// The given amount of bytes.
+    Bytes(usize),
+    /// A struct of the given type.
+    Struct(String),
+    /// An array of the given amount of elements.
+    Array(usize),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unsigned(value) => write!(f, "{} (0x{:X})", value, value),
+            Value::Signed(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Variant(value, Some(name)) => write!(f, "{} ({})", name, value),
+            Value::Variant(value, None) => write!(f, "{}", value),
+            Value::Bytes(count) => write!(f, "{} bytes", count),
+            Value::Struct(name) => write!(f, "{}", name),
+            Value::Array(count) => write!(f, "[{}]", count),
+        }
+    }
+}
+
+/// An error found while parsing or applying a [`Template`].
+///
+/// [`Template`]: struct.Template.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The text format was malformed at the given line.
+    Syntax(usize, String),
+    /// A type was used but never defined.
+    UndefinedType(String),
+    /// An array was sized by a field that doesn't come before it.
+    UndefinedField(String),
+    /// An array was sized by a field that isn't an integer, or is negative.
+    InvalidCount(String),
+    /// A field goes past the end of the data.
+    OutOfBounds(String),
+    /// Structures are nested too deep, likely because one contains itself.
+    TooDeep(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Syntax(line, message) => write!(f, "line {}: {}", line, message),
+            TemplateError::UndefinedType(name) => write!(f, "undefined type `{}`", name),
+            TemplateError::UndefinedField(name) => write!(f, "undefined field `{}`", name),
+            TemplateError::InvalidCount(path) => write!(f, "`{}` can't be used as a count", path),
+            TemplateError::OutOfBounds(path) => write!(f, "`{}` goes past the end of the data", path),
+            TemplateError::TooDeep(path) => write!(f, "`{}` is nested too deep", path),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+fn parse_endianness(word: &str) -> Option<Endianness> {
+    match word {
+        "little" => Some(Endianness::Little),
+        "big" => Some(Endianness::Big),
+        _ => None,
+    }
+}
+
+fn parse_type(text: &str, line: usize) -> Result<Type, TemplateError> {
+    let text = text.trim();
+
+    if text.starts_with('[') && text.ends_with(']') {
+        let inner = &text[1..text.len() - 1];
+        let separator = inner
+            .rfind(';')
+            .ok_or_else(|| TemplateError::Syntax(line, "expected `[type; count]`".into()))?;
+        let element = parse_type(&inner[..separator], line)?;
+        let count = inner[separator + 1..].trim();
+        let count = if is_identifier(count) {
+            Count::Field(count.into())
+        } else {
+            Count::Fixed(
+                super::parse_offset(count, 0)
+                    .map_err(|error| TemplateError::Syntax(line, error.to_string()))?,
+            )
+        };
+
+        Ok(Type::array(element, count))
+    } else if let Some(ty) = Type::primitive(text) {
+        Ok(ty)
+    } else if is_identifier(text) {
+        Ok(Type::named(text))
+    } else {
+        Err(TemplateError::Syntax(line, format!("invalid type `{}`", text)))
+    }
+}
+
+/// Collects the lines of a block until its closing brace.
+fn block<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    start: usize,
+) -> Result<Vec<(usize, &'a str)>, TemplateError> {
+    let mut contents = Vec::new();
+
+    for (number, line) in lines {
+        if line == "}" {
+            return Ok(contents);
+        }
+
+        contents.push((number, line));
+    }
+
+    Err(TemplateError::Syntax(start, "unclosed `{`".into()))
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Count, Endianness, Enum, Struct, Template, TemplateError, Type, Value};
+
+    const TEXT: &str = "
+        # A made up format
+        endian big
+
+        struct Header {
+            magic: [u8; 4]
+            kind: Kind
+            count: u16
+            entries: [Entry; count]
+        }
+
+        struct Entry little {
+            offset: u32
+            delta: i16
+        }
+
+        enum Kind: u8 {
+            Image = 1
+            Sound = 2
+        }
+    ";
+
+    fn data() -> Vec<u8> {
+        vec![
+            b'D', b'E', b'M', b'O', 2, 0x00, 0x02,
+            0x10, 0x00, 0x00, 0x00, 0xFF, 0xFF,
+            0x20, 0x00, 0x00, 0x00, 0x02, 0x00,
+        ]
+    }
+
+    #[test]
+    fn test_parse() {
+        let built = Template::new("Header")
+            .endianness(Endianness::Big)
+            .define_struct(
+                Struct::new("Header")
+                    .field("magic", Type::array(Type::U8, Count::Fixed(4)))
+                    .field("kind", Type::named("Kind"))
+                    .field("count", Type::U16)
+                    .field("entries", Type::array(Type::named("Entry"), Count::Field("count".into()))),
+            )
+            .define_struct(
+                Struct::new("Entry")
+                    .endianness(Endianness::Little)
+                    .field("offset", Type::U32)
+                    .field("delta", Type::I16),
+            )
+            .define_enum(Enum::new("Kind", Type::U8).variant(1, "Image").variant(2, "Sound"));
+
+        assert_eq!(Template::parse(TEXT), Ok(built));
+        assert_eq!(
+            Template::parse("struct A {\n  a u8\n}"),
+            Err(TemplateError::Syntax(2, "expected `name: type`".into())),
+        );
+        assert_eq!(
+            Template::parse("struct A {\n  a: u8"),
+            Err(TemplateError::Syntax(1, "unclosed `{`".into())),
+        );
+    }
+
+    #[test]
+    fn test_signed_enum() {
+        let parsed = Template::parse("
+            struct A {
+                sign: Sign
+            }
+
+            enum Sign: i8 {
+                Minus = -1
+                Plus = 0x01
+            }
+        ").unwrap();
+        let built = Template::new("A")
+            .define_struct(Struct::new("A").field("sign", Type::named("Sign")))
+            .define_enum(Enum::new("Sign", Type::I8).variant(0xFF, "Minus").variant(1, "Plus"));
+
+        assert_eq!(parsed, built);
+        assert_eq!(
+            parsed.apply(&vec![0xFF], 0).unwrap().children[0].value,
+            Value::Variant(-1i64 as u64, Some("Minus".into())),
+        );
+        assert_eq!(
+            Enum::new("Sign", Type::I8).variant(-1i64 as u64, "Minus"),
+            Enum::new("Sign", Type::I8).variant(0xFF, "Minus"),
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let template = Template::parse(TEXT).unwrap();
+        let root = template.apply(&data(), 0).unwrap();
+
+        assert_eq!(root.range, 0..19);
+        assert_eq!(root.children[0].value, Value::Bytes(4));
+        assert_eq!(root.children[1].value, Value::Variant(2, Some("Sound".into())));
+        assert_eq!(root.children[2].value, Value::Unsigned(2));
+
+        let delta = root.field_at(11).unwrap();
+        assert_eq!(delta.path, "Header.entries[0].delta");
+        assert_eq!(delta.range, 11..13);
+        assert_eq!(delta.value, Value::Signed(-1));
+
+        assert_eq!(root.field_at(15).unwrap().value, Value::Unsigned(0x20));
+        assert_eq!(root.leaves().len(), 7);
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        let template = Template::parse(TEXT).unwrap();
+
+        // Two entries take at least 12 bytes, so they're not read at all
+        assert_eq!(
+            template.apply(&data()[..15].to_vec(), 0),
+            Err(TemplateError::OutOfBounds("Header.entries".into())),
+        );
+
+        let recursive = Template::parse("struct A {\n  a: A\n}").unwrap();
+        assert!(matches!(recursive.apply(&vec![0; 4], 0), Err(TemplateError::TooDeep(_))));
+
+        let undefined = Template::parse("struct A {\n  a: B\n}").unwrap();
+        assert_eq!(undefined.apply(&vec![0; 4], 0), Err(TemplateError::UndefinedType("B".into())));
+    }
+}
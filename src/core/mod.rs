@@ -2,6 +2,9 @@
 
 use std::ops::Range;
 
+#[cfg(feature = "hexview")]
+pub mod hexview;
+
 /// A iterator over a string slice in (non-overlapping) chunks (`chunk_size` elements at a time),
 /// starting at the beginning of the slice.
 ///
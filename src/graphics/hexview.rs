@@ -11,7 +11,7 @@ use iced_graphics::{
 use iced_native::{mouse, Background, Color, Point, Rectangle};
 use crate::{
     core::range_intersect,
-    native::hexview,
+    native::hexview::{self, ByteSource},
     style::hexview as style,
 };
 
@@ -37,6 +37,10 @@ const CURSOR_MESH: (&[Vertex2D], &[u32]) = (&[
 const CURSOR_RIGHT_VERTEX: ([usize; 3], [usize; 2]) = ([5, 6, 7], [8, 9]);
 const CURSOR_PADDING: f32 = 4.0;
 
+/// Rows drawn past each edge of the viewport, so scrolling doesn't reveal
+/// rows before the next frame catches up.
+const OVERSCAN_ROWS: usize = 2;
+
 /// Spacing between each row in an [`Hexview`].
 ///
 /// [`Hexview`]: struct.Heview.html
@@ -49,12 +53,146 @@ pub const LINE_SPACING: f32 = 8.0;
 ///
 /// [`Hexview`]: struct.Heview.html
 pub const MARGINS: Vector = Vector::new(10.0, 10.0);
+
+/// Width of the scrollbar in an [`Hexview`].
+///
+/// [`Hexview`]: struct.Heview.html
+pub const SCROLLBAR_WIDTH: f32 = 10.0;
 const HEX_CHARS: &[u8] = b"0123456789ABCDEF\
+                           0123456789ABCDEF\
+                           0123456789ABCDEF\
                            0123456789ABCDEF";
-const OFFSET_REFERENCE: &'static str = "00000000";
-const BYTES_HEADER: &'static str = "00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F \
-                                    10 11 12 13 14 15 16 17 18 19 1A 1B 1C 1D 1E 1F";
-const ASCII_RANGE: Range<u8> = 32..128;
+/// Shown in the character pane for the bytes after the first one of a
+/// character.
+const CONTINUATION: char = '\u{B7}';
+const GOTO_LABEL: &str = "Go to: ";
+/// What the go-to overlay is wide enough to show.
+const GOTO_REFERENCE: &str = "Go to: 0x0000000000000000_";
+
+/// Where the digits of each byte of a row go in the byte pane, counted in
+/// characters.
+///
+/// Bytes are shown in groups spaced apart, byte-swapped when they are little
+/// endian. The last group of a row may be shorter than the others.
+#[derive(Debug, Clone, Copy)]
+struct Cells {
+    radix: hexview::Radix,
+    group_size: usize,
+    swapped: bool,
+    column_count: usize,
+}
+
+impl Cells {
+    fn new(
+        radix: hexview::Radix,
+        group_size: usize,
+        endianness: hexview::Endianness,
+        column_count: usize,
+    ) -> Self {
+        Self {
+            radix,
+            group_size: group_size.max(1),
+            swapped: endianness == hexview::Endianness::Little,
+            column_count,
+        }
+    }
+
+    /// The characters a byte takes.
+    fn digits(&self) -> usize {
+        self.radix.digits()
+    }
+
+    /// The characters a whole group takes, with the space after it.
+    fn group_stride(&self) -> usize {
+        self.group_size * self.digits() + 1
+    }
+
+    /// The amount of bytes in the group containing `column`.
+    fn group_len(&self, column: usize) -> usize {
+        let group_start = column - column % self.group_size;
+
+        self.group_size.min(self.column_count - group_start)
+    }
+
+    /// The character where the byte at `column` starts.
+    fn start(&self, column: usize) -> usize {
+        let index = column % self.group_size;
+        let slot = if self.swapped { self.group_len(column) - 1 - index } else { index };
+
+        column / self.group_size * self.group_stride() + slot * self.digits()
+    }
+
+    /// The characters a whole row takes.
+    fn len(&self) -> usize {
+        let last = self.column_count - 1;
+
+        last / self.group_size * self.group_stride() + self.group_len(last) * self.digits()
+    }
+
+    /// The columns in the order they are shown.
+    fn display_order(&self) -> Vec<usize> {
+        let mut columns: Vec<usize> = (0..self.column_count).collect();
+
+        columns.sort_by_key(|column| self.start(*column));
+        columns
+    }
+
+    /// The characters covering the columns `start..end`, one range for each
+    /// group unless they touch.
+    fn spans(&self, start: usize, end: usize) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        let mut column = start;
+
+        while column < end {
+            let group_end = (column - column % self.group_size + self.group_size).min(end);
+            let starts = (column..group_end).map(|column| self.start(column));
+            let span = starts.clone().min().unwrap()..(starts.max().unwrap() + self.digits());
+
+            match spans.last_mut() {
+                Some(last) if last.end + 1 == span.start => last.end = span.end,
+                _ => spans.push(span),
+            }
+
+            column = group_end;
+        }
+
+        spans
+    }
+
+    /// A row of zeros where the digits go, which measures like a row of data
+    /// so nothing has to be drawn to know where a byte is.
+    fn shape(&self) -> String {
+        let mut shape = vec![b' '; self.len()];
+
+        for column in 0..self.column_count {
+            let start = self.start(column);
+
+            for digit in &mut shape[start..(start + self.digits())] {
+                *digit = b'0';
+            }
+        }
+
+        String::from_utf8(shape).unwrap()
+    }
+
+    /// The column header, with the index of the first byte of each group
+    /// over it.
+    fn header(&self) -> String {
+        let mut header = String::new();
+
+        for column in (0..self.column_count).step_by(self.group_size) {
+            let start = self.start(column);
+
+            while header.len() < start {
+                header.push(' ');
+            }
+
+            self.radix.write_byte(column as u8, &mut header);
+        }
+
+        header
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum SpanType {
@@ -76,25 +214,34 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
         &self,
         bounds: Rectangle,
         cursor_position: Point,
+        scroll: hexview::ScrollPosition,
         font: Font,
         text_size: f32,
         column_count: usize,
+        radix: hexview::Radix,
+        group_size: usize,
+        endianness: hexview::Endianness,
+        offset_format: hexview::OffsetFormat,
+        base_address: u64,
         extend_line: bool,
-        bytes: &[u8],
-    ) -> Option<usize> {
-        let row_count = (bytes.len() as f32 / column_count as f32).ceil() as usize;
+        bytes_len: usize,
+    ) -> Option<(usize, hexview::Pane)> {
+        let row_count = (bytes_len as f64 / column_count as f64).ceil() as usize;
+        let row_height = text_size + LINE_SPACING;
 
+        let offset_digits = offset_format.digits(base_address.saturating_add(bytes_len as u64));
         let offset_width = self.measure(
-            OFFSET_REFERENCE,
+            &offset_format.format(0, offset_digits),
             text_size,
             font,
             bounds.size(),
         ).0;
 
-        let bytes_header_slice = &BYTES_HEADER[0..(column_count * 3 - 1)];
+        let cells = Cells::new(radix, group_size, endianness, column_count);
+        let shape = cells.shape();
 
         let bytes_width = self.measure(
-            bytes_header_slice,
+            &shape,
             text_size,
             font,
             bounds.size(),
@@ -102,67 +249,88 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
 
         let start_of_bytes = Point::new(
             bounds.x.floor() + MARGINS.x + offset_width + MARGINS.x * 2.0,
-            bounds.y.floor() + MARGINS.y + text_size + LINE_SPACING,
-        );
-
-        let size_of_bytes  = Size::new(
-            bytes_width,
-            start_of_bytes.y + row_count as f32 * (text_size + LINE_SPACING) - LINE_SPACING,
+            bounds.y.floor() + MARGINS.y + row_height,
         );
 
-        let bytes_bounds = Rectangle::new(start_of_bytes, size_of_bytes);
+        // Only the remainder of the scroll is in pixels, the row it's in is
+        // worked out directly
+        let rows_down = ((cursor_position.y - start_of_bytes.y + scroll.remainder) / row_height).floor();
+        let row = scroll.row as f64 + rows_down as f64;
 
-        if !bytes_bounds.contains(cursor_position) {
+        if row < 0.0 || row_count == 0 {
             return None;
         }
 
-        let pair_size = self.measure(
-            "FF",
-            text_size,
-            font,
-            bounds.size(),
-        );
-        let space_width = self.measure(
-            " ",
+        let row = match row as usize {
+            row if row < row_count => row,
+            _ if extend_line => row_count - 1,
+            _ => return None,
+        };
+
+        if cursor_position.x < start_of_bytes.x || cursor_position.x > start_of_bytes.x + bytes_width {
+            let char_width = self.measure("0", text_size, font, bounds.size()).0;
+
+            return text_column(
+                start_of_bytes.x + bytes_width + MARGINS.x * 2.0,
+                cursor_position.x,
+                char_width,
+                column_count,
+                extend_line,
+            ).map(|column| (row * column_count + column, hexview::Pane::Text));
+        }
+
+        let measure = |content: &str| self.measure(
+            content,
             text_size,
             font,
             bounds.size(),
         ).0;
+        let cell_width = measure(&shape[0..cells.digits()]);
+        let space_width = measure(" ");
 
-        let mut cursor = None;
+        // Every row has its cells in the same places
+        let mut cell_positions: Vec<(f32, f32)> = (0..column_count)
+            .map(|column| (measure(&shape[0..cells.start(column)]), cell_width))
+            .collect();
 
-        for row in 0..row_count {
-            let pair_y = (text_size + LINE_SPACING) * row as f32;
+        if extend_line {
+            cell_positions.push((bytes_width, space_width));
+        }
 
-            let mut bytes_x = 0.0;
-            let mut pair_positions = (0..column_count)
-                .enumerate()
-                .fold(Vec::new(), |mut acc, (i, _pair)| {
-                    acc.push((Point::new(bytes_x, pair_y), pair_size.0));
-                    bytes_x += pair_size.0 + if i != column_count -1 { space_width } else { 0.0 };
-                    acc
-                });
+        let x = cursor_position.x - start_of_bytes.x;
 
-            if extend_line {
-                pair_positions.push((Point::new(bytes_x, pair_y), space_width));
-            }
+        cell_positions
+            .iter()
+            .position(|(start, width)| *start <= x && x <= start + width)
+            .map(|column| (row * column_count + column, hexview::Pane::Bytes))
+    }
 
-            for (i, (position, width)) in pair_positions.iter().enumerate() {
-                let bound  = Rectangle {
-                    x: start_of_bytes.x + position.x,
-                    y: start_of_bytes.y + position.y,
-                    width: *width,
-                    height: pair_size.0,
-                };
+    fn width(
+        &self,
+        font: Font,
+        text_size: f32,
+        column_count: usize,
+        radix: hexview::Radix,
+        group_size: usize,
+        endianness: hexview::Endianness,
+        offset_format: hexview::OffsetFormat,
+        base_address: u64,
+        bytes_len: usize,
+    ) -> f32 {
+        let measure = |content: &str| self.measure(
+            content,
+            text_size,
+            font,
+            Size::INFINITY,
+        ).0;
 
-                if bound.contains(cursor_position) {
-                    cursor = Some(row * column_count + i);
-                    break;
-                }
-            }
-        }
+        let offset_digits = offset_format.digits(base_address.saturating_add(bytes_len as u64));
+        let offset_width = measure(&offset_format.format(0, offset_digits));
+        let bytes_width = measure(&Cells::new(radix, group_size, endianness, column_count).shape());
+        let text_width = measure(std::str::from_utf8(&HEX_CHARS[0..column_count]).unwrap());
 
-        cursor
+        // Margins around and between the panes, like when drawing
+        MARGINS.x * 6.0 + offset_width + bytes_width + text_width + SCROLLBAR_WIDTH
     }
 
     fn measure(
@@ -178,7 +346,8 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
     fn draw(
         &mut self,
         bounds: Rectangle,
-        _cursor_position: Point,
+        cursor_position: Point,
+        viewport: &Rectangle,
         style_sheet: &Self::Style,
         text_size: f32,
         column_count: usize,
@@ -188,8 +357,25 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
         debug_enabled: bool,
         header_font: Font,
         data_font: Font,
-        selection: &Option<(usize, usize)>,
-        data: &[u8],
+        selection: Option<Range<usize>>,
+        radix: hexview::Radix,
+        group_size: usize,
+        endianness: hexview::Endianness,
+        offset_format: hexview::OffsetFormat,
+        base_address: u64,
+        digit: Option<usize>,
+        encoding: &dyn hexview::Encoding,
+        pane: hexview::Pane,
+        insert_mode: bool,
+        scroll: hexview::ScrollPosition,
+        scrollbar: Option<hexview::Scrollbar>,
+        goto_input: Option<&hexview::GotoInput>,
+        differences: &[Range<usize>],
+        matches: &[Range<usize>],
+        regions: &[hexview::Region],
+        template_fields: &[Range<usize>],
+        hover_label: Option<&str>,
+        source: &dyn ByteSource,
     ) -> Self::Output {
 
         let style = style_sheet.active();
@@ -207,19 +393,50 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
             border_width: 0,
             border_color: Color::BLACK,
         };
-        let line_count = (data.len() as f32 / column_count as f32).ceil() as usize;
+        let data_len = source.len();
+
+        // The cursor may be right past the end of the data when inserting
+        let line_count = (data_len.max(cursor + 1) as f64 / column_count as f64).ceil() as usize;
         let data_y = MARGINS.y + text_size + LINE_SPACING;
 
+        // Rows are placed from the first visible one, so their positions stay
+        // small no matter how far the data is scrolled
+        let row_y = |row: usize| {
+            bounds_pos.1 + data_y + ((row as f64 - scroll.row as f64) as f32) * (text_size + LINE_SPACING)
+        };
+
+        // Rows scroll below the header, which stays in place
+        let rows_bounds = Rectangle {
+            x: bounds_pos.0,
+            y: bounds_pos.1 + data_y,
+            width: bounds_size.0,
+            height: (bounds_size.1 - data_y - MARGINS.y).max(0.0),
+        };
+        let visible_lines = match viewport.intersection(&rows_bounds) {
+            Some(visible_bounds) => visible_rows(
+                scroll.row,
+                rows_bounds.y - scroll.remainder,
+                text_size + LINE_SPACING,
+                &visible_bounds,
+                line_count,
+            ),
+            None => 0..0,
+        };
+
+        // Every offset is as wide as the widest one, which is measured with
+        // zeros like the byte pane
+        let offset_digits = offset_format.digits(base_address.saturating_add(data_len as u64));
         let offset_width = self.measure(
-            OFFSET_REFERENCE,
+            &offset_format.format(0, offset_digits),
             text_size,
             header_font,
             bounds.size(),
         ).0;
 
-        let bytes_header_slice = &BYTES_HEADER[0..(column_count * 3 - 1)];
+        let cells = Cells::new(radix, group_size, endianness, column_count);
+        let shape = cells.shape();
         let bytes_header_width = self.measure(
-            bytes_header_slice,
+            &shape,
             text_size,
             header_font,
             bounds.size(),
@@ -242,7 +459,7 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
         };
 
         let bytes_header = Primitive::Text {
-            content: bytes_header_slice.into(),
+            content: cells.header(),
             bounds: Rectangle {
                 x: bounds_pos.0 + right_of_offset + MARGINS.x * 2.0,
                 y: bounds_pos.1 + MARGINS.y,
@@ -266,14 +483,56 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
         ).0;
 
         let start_of_bytes = right_of_offset + MARGINS.x * 2.0;
-        let mut byte_buffers = Vec::new();
+        let start_of_text = right_of_bytes_header + MARGINS.x * 2.0;
+        let mut row_buffer = vec![0; column_count];
+
+        // Covers the columns `start..end` of a row in both panes. The headers
+        // have the same shape as a line of data, so they are measured instead.
+        let highlight = |start: usize, end: usize, line_y: f32, color: Color| {
+            let measure = |content: &str| self.measure(
+                content,
+                text_size,
+                data_font,
+                bounds.size(),
+            ).0;
+            let quad = |x: f32, width: f32| Primitive::Quad {
+                bounds: Rectangle {
+                    x: bounds_pos.0 + x,
+                    y: line_y,
+                    width,
+                    height: text_size,
+                },
+                background: Background::Color(color),
+                border_radius: 0,
+                border_width: 0,
+                border_color: Color::BLACK,
+            };
 
-        let lines: Vec<Primitive> = (0..line_count).map(|i| {
+            let mut quads: Vec<Primitive> = cells
+                .spans(start, end)
+                .into_iter()
+                .map(|span| quad(
+                    start_of_bytes + measure(&shape[0..span.start]),
+                    measure(&shape[span]),
+                ))
+                .collect();
+
+            quads.push(quad(
+                start_of_text + measure(&ascii_hex_chars[0..start]),
+                measure(&ascii_hex_chars[start..end]),
+            ));
+
+            group(quads)
+        };
+
+        let lines: Vec<Primitive> = visible_lines.clone().map(|i| {
             let lower_bound = column_count * i;
-            let upper_bound = (lower_bound + column_count).min(data.len());
-            let data_slice = &data[lower_bound..upper_bound];
+            let read_count = source.read(lower_bound, &mut row_buffer);
+            let upper_bound = lower_bound + read_count;
+            let data_slice = &row_buffer[..read_count];
+            let glyphs = hexview::encoding::glyphs(encoding, source, lower_bound..upper_bound);
             let line_x = bounds_pos.0 + MARGINS.x;
-            let line_y = bounds_pos.1 + data_y + i as f32 * (text_size + LINE_SPACING);
+            let line_y = row_y(i);
             let np_have_color = style.non_printable_color.is_some();
 
             let mut byte_spans = Vec::new();
@@ -283,12 +542,21 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
             let mut np_offset = 0;
             let mut data_x = start_of_bytes;
 
-            // Generate hexpairs in spans that will be transformed to text later
-            let byte_buffer = data_slice
-                .iter()
-                .enumerate()
-                .fold(String::new(), |mut acc, (i, b)| {
-                    if ASCII_RANGE.contains(b) {
+            // Generate the digits of each byte in spans that will be transformed to text later
+            let byte_buffer = cells
+                .display_order()
+                .into_iter()
+                .filter(|column| *column < data_slice.len())
+                .fold(String::new(), |mut acc, column| {
+                    let b = &data_slice[column];
+
+                    // Spaces between groups, or where missing bytes of a
+                    // byte-swapped group would be
+                    while acc.len() < cells.start(column) {
+                        acc.push(' ');
+                    }
+
+                    if glyphs[column].is_printable() {
                         // Update printable offset and generate non-printable span
                         if np_control {
                             printable_offset = acc.len();
@@ -326,15 +594,7 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                         }
                     }
 
-                    let high = HEX_CHARS[(b >> 4) as usize] as char;
-                    let low = HEX_CHARS[(b & 0xF) as usize] as char;
-
-                    acc.push(high);
-                    acc.push(low);
-
-                    if i != data_slice.len() - 1 {
-                        acc.push(' ');
-                    }
+                    radix.write_byte(*b, &mut acc);
 
                     acc
                 });
@@ -360,10 +620,10 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
             np_offset = 0;
 
             // Generate the ASCII repesentation in spans that will be transformed to text later
-            let ascii_buffer = data_slice
+            let ascii_buffer = glyphs
                 .iter()
-                .fold(String::new(), |mut acc, b| {
-                    if ASCII_RANGE.contains(b) {
+                .fold(String::new(), |mut acc, glyph| {
+                    if let hexview::Glyph::Char(c) = glyph {
                         if np_control {
                             printable_offset = acc.len();
 
@@ -384,7 +644,7 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                             np_control = false;
                         }
 
-                        acc.push(*b as char);
+                        acc.push(*c);
                     } else {
                         if !np_control {
                             np_offset = acc.len();
@@ -400,7 +660,10 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                             np_control = true;
                         }
 
-                        acc.push('.');
+                        acc.push(match glyph {
+                            hexview::Glyph::Continuation => CONTINUATION,
+                            _ => '.',
+                        });
                     }
 
                     acc
@@ -500,83 +763,86 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                     acc
                 });
 
-            let selection_prim = if let Some((start, end)) = selection {
-                let selection = *start..*end;
-                let row_range = lower_bound..upper_bound;
-                let intersection = range_intersect(row_range, selection);
+            // Template fields are sorted like matches, and their colors
+            // alternate so that neighbours can be told apart
+            let first_field = first_in_row(template_fields, lower_bound);
 
-                if !intersection.is_empty() {
-                    let intersection_size = intersection.end - intersection.start;
-                    let start = intersection.start - lower_bound;
-                    let end = start + intersection_size;
-
-                    let (bytes_pre_slice, ascii_pre_slice) = if start > 0 {
-                        (
-                            &byte_buffer[0..(start * 3).min(byte_buffer.len())],
-                            &ascii_buffer[0..start],
-                        )
-                    } else {
-                        ("", "")
-                    };
+            let field_prims = template_fields[first_field..]
+                .iter()
+                .enumerate()
+                .take_while(|(_, range)| range.start < upper_bound)
+                .map(|(index, range)| {
+                    let intersection = range_intersect(lower_bound..upper_bound, range.clone());
+
+                    highlight(
+                        intersection.start - lower_bound,
+                        intersection.end - lower_bound,
+                        line_y,
+                        style.field_colors[(first_field + index) % 2],
+                    )
+                })
+                .collect();
 
-                    let bytes_slice = &byte_buffer[(start * 3)..(end * 3).min(byte_buffer.len())];
-                    let ascii_slice = &ascii_buffer[start..end];
-                    let bytes_width = self.measure(
-                        bytes_slice,
-                        text_size,
-                        data_font,
-                        bounds.size(),
-                    ).0;
-                    let ascii_width = self.measure(
-                        ascii_slice,
-                        text_size,
-                        data_font,
-                        bounds.size(),
-                    ).0;
+            // Regions aren't sorted, and the later ones go on top
+            let region_prims = regions
+                .iter()
+                .filter(|region| region.range.start < upper_bound && region.range.end > lower_bound)
+                .map(|region| {
+                    let intersection = range_intersect(lower_bound..upper_bound, region.range.clone());
+
+                    highlight(
+                        intersection.start - lower_bound,
+                        intersection.end - lower_bound,
+                        line_y,
+                        region.color,
+                    )
+                })
+                .collect();
 
-                    let bytes_x = self.measure(
-                        bytes_pre_slice,
-                        text_size,
-                        data_font,
-                        bounds.size(),
-                    ).0;
-                    let ascii_x = self.measure(
-                        ascii_pre_slice,
-                        text_size,
-                        data_font,
-                        bounds.size(),
-                    ).0;
+            let difference_prims = differences[first_in_row(differences, lower_bound)..]
+                .iter()
+                .take_while(|range| range.start < upper_bound)
+                .map(|range| {
+                    let intersection = range_intersect(lower_bound..upper_bound, range.clone());
+
+                    highlight(
+                        intersection.start - lower_bound,
+                        intersection.end - lower_bound,
+                        line_y,
+                        style.diff_color,
+                    )
+                })
+                .collect();
 
-                    group(vec![
-                        // Bytes
-                        Primitive::Quad {
-                            bounds: Rectangle {
-                                x: bounds_pos.0 + start_of_bytes + bytes_x,
-                                y: line_y,
-                                width: bytes_width,
-                                height: text_size,
-                            },
-                            background: Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5)),
-                            border_radius: 0,
-                            border_width: 0,
-                            border_color: Color::BLACK,
-                        },
+            // Matches are sorted, so only the ones around this row are looked at
+            let first_match = first_in_row(matches, lower_bound);
 
-                        // Ascii
-                        Primitive::Quad {
-                            bounds: Rectangle {
-                                x: right_of_bytes_header + MARGINS.x * 2.0 + ascii_x,
-                                y: line_y,
-                                width: ascii_width,
-                                height: text_size,
-                            },
-                            background: Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5)),
-                            border_radius: 0,
-                            border_width: 0,
-                            border_color: Color::BLACK,
-                        },
-                    ])
+            let match_prims = matches[first_match..]
+                .iter()
+                .take_while(|range| range.start < upper_bound)
+                .map(|range| {
+                    let intersection = range_intersect(lower_bound..upper_bound, range.clone());
+
+                    highlight(
+                        intersection.start - lower_bound,
+                        intersection.end - lower_bound,
+                        line_y,
+                        style.match_color,
+                    )
+                })
+                .collect();
+
+            let selection_prim = if let Some(selection) = selection.clone() {
+                let row_range = lower_bound..upper_bound;
+                let intersection = range_intersect(row_range, selection);
 
+                if !intersection.is_empty() {
+                    highlight(
+                        intersection.start - lower_bound,
+                        intersection.end - lower_bound,
+                        line_y,
+                        Color::from_rgba(0.0, 0.0, 0.0, 0.5),
+                    )
                 } else {
                     Primitive::None
                 }
@@ -587,7 +853,10 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
             let primitives = vec![
                 // Offset
                 Primitive::Text {
-                    content: format!("{:08X}", i * column_count),
+                    content: offset_format.format(
+                        base_address.wrapping_add((i * column_count) as u64),
+                        offset_digits,
+                    ),
                     bounds: Rectangle {
                         x: line_x,
                         y: line_y,
@@ -601,6 +870,18 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                     vertical_alignment: VerticalAlignment::Top,
                 },
 
+                // Template fields
+                group(field_prims),
+
+                // Regions
+                group(region_prims),
+
+                // Differences
+                group(difference_prims),
+
+                // Matches
+                group(match_prims),
+
                 // Bytes
                 group(byte_prims),
 
@@ -612,7 +893,6 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
 
             ];
 
-            byte_buffers.push(byte_buffer);
             group(primitives)
         }).collect();
 
@@ -646,27 +926,51 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
 
         let line = cursor / column_count;
         let line_offset = cursor % column_count;
-        let line_str = &byte_buffers[line];
+        let cursor_y = row_y(line);
 
+        // The headers have the same shape as a line of data, so we can measure
+        // the cursor position without the line being drawn at all.
         let byte_offset = self.measure(
-            &line_str[0..(line_offset * 3)],
+            &shape[0..cells.start(line_offset)],
             text_size,
             data_font,
             bounds.size(),
         ).0;
 
         let pair_width = self.measure(
-            &line_str[(line_offset * 3)..(line_offset * 3 + 2)],
+            &shape[cells.start(line_offset)..(cells.start(line_offset) + cells.digits())],
+            text_size,
+            data_font,
+            bounds.size(),
+        ).0;
+
+        let char_offset = self.measure(
+            &ascii_hex_chars[0..line_offset],
+            text_size,
+            data_font,
+            bounds.size(),
+        ).0;
+
+        let char_width = self.measure(
+            &shape[cells.start(line_offset)..(cells.start(line_offset) + 1)],
             text_size,
             data_font,
             bounds.size(),
         ).0;
 
-        let cursor_width = pair_width + CURSOR_PADDING;
+        // Horizontal position and width of the cursor in each pane
+        let byte_cell = (start_of_bytes + byte_offset, pair_width);
+        let text_cell = (right_of_bytes_header + MARGINS.x * 2.0 + char_offset, char_width);
+        let (active_cell, ghost_cell) = match pane {
+            hexview::Pane::Bytes => (byte_cell, text_cell),
+            hexview::Pane::Text => (text_cell, byte_cell),
+        };
+
+        let cursor_width = active_cell.1 + CURSOR_PADDING;
 
         let cursor_mesh_pos = [
-            start_of_bytes + byte_offset + pair_width - pair_width / 2.0 - cursor_width / 2.0,
-            MARGINS.y + text_size + LINE_SPACING + 12.0 + ((text_size + LINE_SPACING) * (cursor / column_count) as f32),
+            active_cell.0 + active_cell.1 / 2.0 - cursor_width / 2.0,
+            cursor_y - bounds_pos.1 + 12.0,
         ];
 
         let cursor_mesh = Mesh2D {
@@ -692,12 +996,68 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
             cursor_mesh.vertices[9].position[1],
         );
 
-        let cursor_prim = Primitive::Translate {
-            translation: Vector::new(bounds_pos.0, bounds_pos.1) + cursor_mesh_pos.into(),
-            content: Box::new(Primitive::Mesh2D {
-                buffers: cursor_mesh,
-                size: cursor_size,
+        // Highlight the digit or character being edited
+        let digit_prim = if let Some(digit) = digit {
+            // Every digit is as wide as a character
+            let digit_x = match pane {
+                hexview::Pane::Text => 0.0,
+                hexview::Pane::Bytes => char_width * digit as f32,
+            };
+            let digit_width = char_width;
+
+            // Inserting shows a caret before the digit instead of covering it
+            let digit_width = if insert_mode { 2.0 } else { digit_width };
+
+            Primitive::Quad {
+                bounds: Rectangle {
+                    x: bounds_pos.0 + active_cell.0 + digit_x,
+                    y: cursor_y,
+                    width: digit_width,
+                    height: text_size,
+                },
+                background: Background::Color(Color {
+                    a: 0.4,
+                    ..style.cursor_color
+                }),
+                border_radius: 0,
+                border_width: 0,
+                border_color: Color::BLACK,
+            }
+        } else {
+            Primitive::None
+        };
+
+        // Where the cursor is in the pane that isn't active
+        let ghost_prim = Primitive::Quad {
+            bounds: Rectangle {
+                x: bounds_pos.0 + ghost_cell.0,
+                y: cursor_y,
+                width: ghost_cell.1,
+                height: text_size,
+            },
+            background: Background::Color(Color {
+                a: 0.2,
+                ..style.cursor_color
             }),
+            border_radius: 0,
+            border_width: 0,
+            border_color: Color::BLACK,
+        };
+
+        let cursor_prim = if visible_lines.contains(&line) {
+            group(vec![
+                ghost_prim,
+                digit_prim,
+                Primitive::Translate {
+                    translation: Vector::new(bounds_pos.0, bounds_pos.1) + cursor_mesh_pos.into(),
+                    content: Box::new(Primitive::Mesh2D {
+                        buffers: cursor_mesh,
+                        size: cursor_size,
+                    }),
+                },
+            ])
+        } else {
+            Primitive::None
         };
 
         let debug_info = if debug_enabled {
@@ -717,7 +1077,7 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                 bytes_header_width,
                 cursor_mesh_pos[0], cursor_mesh_pos[1],
                 cursor_size.width, cursor_size.height,
-                data.len(), byte_offset, test_offset,
+                data_len, byte_offset, test_offset,
                 bounds_pos.0, bounds_pos.1, bounds_size.0,
                 bounds_size.1,
             );
@@ -737,7 +1097,7 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                     content: debug_text,
                     bounds: Rectangle {
                         x: bounds_pos.0 + MARGINS.x,
-                        y: bounds_pos.1 + data_y + line_count as f32 * (text_size + LINE_SPACING),
+                        y: row_y(line_count),
                         width: 400.0,
                         height: text_size * debug_line_count as f32,
                     },
@@ -752,6 +1112,143 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
             Primitive::None
         };
 
+        let rows = Primitive::Clip {
+            bounds: rows_bounds,
+            offset: Vector::new(0, scroll.remainder.round() as u32),
+            content: Box::new(group(vec![
+                group(lines),
+                cursor_prim,
+                debug_info,
+            ])),
+        };
+
+        let scrollbar_prim = if let Some(scrollbar) = scrollbar {
+            group(vec![
+                Primitive::Quad {
+                    bounds: scrollbar.bounds,
+                    background: Background::Color(style.scrollbar_color),
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::BLACK,
+                },
+                Primitive::Quad {
+                    bounds: scrollbar.scroller,
+                    background: Background::Color(style.scroller_color),
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::BLACK,
+                },
+            ])
+        } else {
+            Primitive::None
+        };
+
+        // The go-to overlay covers the end of the header
+        let goto_prim = if let Some(input) = goto_input {
+            let width = self.measure(GOTO_REFERENCE, text_size, data_font, bounds.size()).0
+                + MARGINS.x;
+            let height = text_size + LINE_SPACING;
+            let outer = Rectangle {
+                x: bounds_pos.0 + bounds_size.0 - width - MARGINS.x,
+                y: bounds_pos.1 + MARGINS.y - LINE_SPACING / 2.0,
+                width,
+                height,
+            };
+            let border_color = if input.is_invalid { style.error_color } else { style.line_color };
+
+            group(vec![
+                Primitive::Quad {
+                    bounds: outer,
+                    background: Background::Color(border_color),
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::BLACK,
+                },
+                Primitive::Quad {
+                    bounds: Rectangle {
+                        x: outer.x + 1.0,
+                        y: outer.y + 1.0,
+                        width: outer.width - 2.0,
+                        height: outer.height - 2.0,
+                    },
+                    background: Background::Color(style.background_color),
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::BLACK,
+                },
+                Primitive::Text {
+                    content: format!("{}{}_", GOTO_LABEL, input.value),
+                    bounds: Rectangle {
+                        x: outer.x + MARGINS.x / 2.0,
+                        y: outer.y + LINE_SPACING / 2.0,
+                        width: width - MARGINS.x,
+                        height: text_size,
+                    },
+                    color: if input.is_invalid { style.error_color } else { style.data_color },
+                    size: text_size,
+                    font: data_font,
+                    horizontal_alignment: HorizontalAlignment::Left,
+                    vertical_alignment: VerticalAlignment::Top,
+                },
+            ])
+        } else {
+            Primitive::None
+        };
+
+        // The label of the hovered region follows the mouse, but stays inside
+        let label_prim = if let Some(label) = hover_label {
+            let width = self.measure(label, text_size, data_font, bounds.size()).0 + MARGINS.x;
+            let height = text_size + LINE_SPACING;
+            let outer = Rectangle {
+                x: (cursor_position.x + MARGINS.x)
+                    .min(bounds_pos.0 + bounds_size.0 - width)
+                    .max(bounds_pos.0),
+                y: (cursor_position.y + text_size + LINE_SPACING)
+                    .min(bounds_pos.1 + bounds_size.1 - height)
+                    .max(bounds_pos.1),
+                width,
+                height,
+            };
+
+            group(vec![
+                Primitive::Quad {
+                    bounds: outer,
+                    background: Background::Color(style.line_color),
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::BLACK,
+                },
+                Primitive::Quad {
+                    bounds: Rectangle {
+                        x: outer.x + 1.0,
+                        y: outer.y + 1.0,
+                        width: outer.width - 2.0,
+                        height: outer.height - 2.0,
+                    },
+                    background: Background::Color(style.background_color),
+                    border_radius: 0,
+                    border_width: 0,
+                    border_color: Color::BLACK,
+                },
+                Primitive::Text {
+                    content: label.into(),
+                    bounds: Rectangle {
+                        x: outer.x + MARGINS.x / 2.0,
+                        y: outer.y + LINE_SPACING / 2.0,
+                        width: width - MARGINS.x,
+                        height: text_size,
+                    },
+                    color: style.data_color,
+                    size: text_size,
+                    font: data_font,
+                    horizontal_alignment: HorizontalAlignment::Left,
+                    vertical_alignment: VerticalAlignment::Top,
+                },
+            ])
+        } else {
+            Primitive::None
+        };
+
         (
             group(vec![
                 back,
@@ -759,21 +1256,90 @@ impl<B: Backend + BackendWithText> hexview::Renderer for Renderer<B> {
                 bytes_separator,
                 bytes_header,
                 ascii_columns,
-                group(lines),
-                cursor_prim,
-                debug_info,
+                rows,
+                scrollbar_prim,
+                goto_prim,
+                label_prim,
             ]),
             mouse::Interaction::default(),
         )
     }
 }
 
+impl<B: Backend + BackendWithText> hexview::diff::Renderer for Renderer<B> {
+    fn draw_diff(&mut self, left: Self::Output, right: Self::Output) -> Self::Output {
+        // Only the side under the mouse asks for something else
+        let interaction = if left.1 == mouse::Interaction::default() { right.1 } else { left.1 };
+
+        (
+            Primitive::Group {
+                primitives: vec![left.0, right.0],
+            },
+            interaction,
+        )
+    }
+}
+
+/// Finds the first of the sorted `ranges` that ends after `lower_bound`, the
+/// start of a row.
+fn first_in_row(ranges: &[Range<usize>], lower_bound: usize) -> usize {
+    match ranges.binary_search_by(|range| {
+        if range.end <= lower_bound {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Greater
+        }
+    }) {
+        Ok(index) | Err(index) => index,
+    }
+}
+
+/// Calculates the column of a mouse position in the text pane, which starts
+/// at `start_x`.
+///
+/// With `extend_line`, the position right past the end of a row is also
+/// considered part of it.
+fn text_column(
+    start_x: f32,
+    cursor_x: f32,
+    char_width: f32,
+    column_count: usize,
+    extend_line: bool,
+) -> Option<usize> {
+    let columns = if extend_line { column_count + 1 } else { column_count };
+
+    if char_width <= 0.0 || cursor_x < start_x || cursor_x > start_x + char_width * columns as f32 {
+        return None;
+    }
+
+    let column = ((cursor_x - start_x) / char_width) as usize;
+
+    Some(column.min(columns - 1))
+}
+
 fn group(primitives: Vec<Primitive>) -> Primitive {
     Primitive::Group {
         primitives,
     }
 }
 
+/// Calculates the range of rows that intersect with `viewport`, including
+/// the overscan, with `first_row` placed at `rows_y`.
+fn visible_rows(
+    first_row: usize,
+    rows_y: f32,
+    row_height: f32,
+    viewport: &Rectangle,
+    row_count: usize,
+) -> Range<usize> {
+    let top = (viewport.y - rows_y) / row_height;
+    let bottom = (viewport.y + viewport.height - rows_y) / row_height;
+    let first = first_row.saturating_add(top.floor().max(0.0) as usize);
+    let last = first_row.saturating_add(bottom.ceil().max(0.0) as usize);
+
+    first.saturating_sub(OVERSCAN_ROWS).min(row_count)..(last + OVERSCAN_ROWS).min(row_count)
+}
+
 fn span_dedup(a: &mut LineSpan, b: &mut LineSpan) -> bool {
     if a.ty == b.ty {
         b.end = a.end;
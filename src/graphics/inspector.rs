@@ -0,0 +1,135 @@
+//! Renderer implementation for an [`Inspector`].
+//!
+//! [`Inspector`]: struct.Inspector.html
+
+use iced_graphics::{
+    Backend, Font, HorizontalAlignment, VerticalAlignment,
+    Primitive, Vector, Renderer, backend::Text as BackendWithText,
+};
+use iced_native::{mouse, Background, Color, Point, Rectangle};
+use crate::{
+    graphics::hexview::LINE_SPACING,
+    native::inspector::{self, cell_bounds, Column, Kind},
+    style::inspector as style,
+};
+
+const HEADERS: [(Column, &str); 3] = [
+    (Column::Name, "Type"),
+    (Column::Little, "Little endian"),
+    (Column::Big, "Big endian"),
+];
+
+/// Shown where the bytes don't make a value.
+const MISSING: &str = "-";
+
+impl<B: Backend + BackendWithText> inspector::Renderer for Renderer<B> {
+    type Style = Box<dyn style::StyleSheet>;
+
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        _cursor_position: Point,
+        style_sheet: &Self::Style,
+        text_size: f32,
+        header_font: Font,
+        data_font: Font,
+        values: &[(Option<String>, Option<String>)],
+        input: Option<&inspector::Input>,
+    ) -> Self::Output {
+        let style = style_sheet.active();
+        let row_height = text_size + LINE_SPACING;
+        let quad = |bounds: Rectangle, color: Color| Primitive::Quad {
+            bounds,
+            background: Background::Color(color),
+            border_radius: 0,
+            border_width: 0,
+            border_color: Color::BLACK,
+        };
+        let text = |cell: Rectangle, content: String, font: Font, color: Color| Primitive::Clip {
+            bounds: cell,
+            offset: Vector::new(0, 0),
+            content: Box::new(Primitive::Text {
+                content,
+                bounds: Rectangle {
+                    x: cell.x + LINE_SPACING / 2.0,
+                    y: cell.y + LINE_SPACING / 2.0,
+                    width: cell.width - LINE_SPACING,
+                    height: text_size,
+                },
+                color,
+                size: text_size,
+                font,
+                horizontal_alignment: HorizontalAlignment::Left,
+                vertical_alignment: VerticalAlignment::Top,
+            }),
+        };
+
+        let mut primitives = vec![quad(bounds, style.background_color)];
+
+        for (column, header) in HEADERS.iter() {
+            let cell = cell_bounds(bounds, row_height, 0, *column);
+
+            primitives.push(text(cell, header.to_string(), header_font, style.header_color));
+        }
+
+        let header_cell = cell_bounds(bounds, row_height, 0, Column::Name);
+        let last_cell = cell_bounds(bounds, row_height, 0, Column::Big);
+
+        primitives.push(quad(
+            Rectangle {
+                x: header_cell.x,
+                y: (header_cell.y + header_cell.height).floor(),
+                width: last_cell.x + last_cell.width - header_cell.x,
+                height: 1.0,
+            },
+            style.line_color,
+        ));
+
+        for (row, (kind, (little, big))) in Kind::ALL.iter().zip(values).enumerate() {
+            let name_cell = cell_bounds(bounds, row_height, row + 1, Column::Name);
+
+            primitives.push(text(name_cell, kind.name().into(), header_font, style.name_color));
+
+            let columns = [
+                (Column::Little, inspector::Endianness::Little, little),
+                (Column::Big, inspector::Endianness::Big, big),
+            ];
+
+            for (column, endianness, value) in columns.iter() {
+                let cell = cell_bounds(bounds, row_height, row + 1, *column);
+                let editing = input.filter(|input| input.kind == *kind && input.endianness == *endianness);
+
+                if let Some(input) = editing {
+                    let border_color = if input.is_invalid { style.error_color } else { style.line_color };
+
+                    primitives.push(quad(cell, border_color));
+                    primitives.push(quad(
+                        Rectangle {
+                            x: cell.x + 1.0,
+                            y: cell.y + 1.0,
+                            width: cell.width - 2.0,
+                            height: cell.height - 2.0,
+                        },
+                        style.input_color,
+                    ));
+                    primitives.push(text(
+                        cell,
+                        format!("{}_", input.value),
+                        data_font,
+                        if input.is_invalid { style.error_color } else { style.value_color },
+                    ));
+                } else {
+                    primitives.push(match value {
+                        Some(value) => text(cell, value.clone(), data_font, style.value_color),
+                        None => text(cell, MISSING.into(), data_font, style.missing_color),
+                    });
+                }
+            }
+        }
+
+        (
+            Primitive::Group { primitives },
+            mouse::Interaction::default(),
+        )
+    }
+}
@@ -2,3 +2,5 @@
 
 #[cfg(feature = "hexview")]
 pub mod hexview;
+#[cfg(feature = "inspector")]
+pub mod inspector;
@@ -5,9 +5,15 @@
 //! # Widget List
 //!
 //! - [`Hexview`]: A widget for viewing binary data.
+//! - [`Diff`]: Two [`Hexview`]s side by side, with their differences
+//!   highlighted.
+//! - [`Inspector`]: Shows the bytes at the cursor of an [`Hexview`] as
+//!   different kinds of values.
 //!
 //! [iced]: https://github.com/hecrj/iced
 //! [`Hexview`]: native/hexview/struct.Hexview.html
+//! [`Diff`]: native/hexview/diff/struct.Diff.html
+//! [`Inspector`]: native/inspector/struct.Inspector.html
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
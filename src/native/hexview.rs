@@ -4,24 +4,59 @@
 
 use iced_native::{
     keyboard, layout, mouse,
-    Clipboard, Element, Event, Font, Hasher, Layout, Length,
+    Clipboard, Color, Element, Event, Font, Hasher, Layout, Length,
     Point, Rectangle, Size, Widget,
 };
 use std::{
+    cell::Cell,
     hash::Hash,
+    io,
     marker::PhantomData,
+    ops::Range,
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
 };
 use crate::{
     core::clamp,
-    graphics::hexview::{LINE_SPACING, MARGINS},
+    graphics::hexview::{LINE_SPACING, MARGINS, SCROLLBAR_WIDTH},
 };
 
+/// Rows scrolled for each line of mouse wheel movement.
+const WHEEL_ROWS: f32 = 3.0;
+
+/// Minimum height of the scroller, so it can still be grabbed with huge data.
+const MIN_SCROLLER_HEIGHT: f32 = 16.0;
+
+/// The most columns an [`Hexview`] can have.
+///
+/// [`Hexview`]: struct.Hexview.html
+pub const MAX_COLUMNS: u8 = 64;
+
+pub use crate::core::hexview::{
+    find_all, find_next, find_regex, parse_offset, ByteRegex, ByteSource, Cancelled, Edit,
+    ExprError, FileSource, History, Pattern, PatternError, PieceTable, RegexError, RegexMatch,
+    ReplaceError, ReplaceMode, Selection, DEFAULT_MAX_MATCH_LEN,
+};
+pub use crate::core::hexview::encoding::{self, Encoding, Glyph};
+pub use crate::core::hexview::template::{self, Endianness, Field, Template, TemplateError};
+pub use crate::core::hexview::diff::{find_differences, update_differences};
+pub use diff::Diff;
+
+pub mod diff;
+
 /// A view into a region of bytes.
 ///
-/// The widget owns the bytes it shows, so be careful when using
-/// [`State::set_bytes`] with huge data.
+/// The bytes are read from a [`ByteSource`] held by the [`State`], and only
+/// the rows being drawn are ever requested from it.
 ///
-/// [`State::set_bytes`]: struct.State.html#method.set_bytes
+/// By default the widget fills the available height and scrolls on its own,
+/// keeping the column header in place. Set its [`height`] to
+/// `Length::Shrink` to lay out every row instead, e.g. inside a
+/// `Scrollable`.
+///
+/// [`height`]: #method.height
+/// [`ByteSource`]: trait.ByteSource.html
+/// [`State`]: struct.State.html
 #[allow(missing_debug_implementations)]
 pub struct Hexview<'a, Message, Renderer: self::Renderer> {
     state: &'a mut State,
@@ -29,26 +64,371 @@ pub struct Hexview<'a, Message, Renderer: self::Renderer> {
     header_font: Font,
     data_font: Font,
     font_size: f32,
-    column_count: u8,
+    columns: Columns,
+    column_count: Cell<u8>,
+    height: Length,
+    read_only: bool,
+    goto_overlay: bool,
+    display_radix: Radix,
+    group_size: u8,
+    endianness: Endianness,
+    snap_to_groups: bool,
+    encoding: Box<dyn Encoding + 'a>,
+    base_address: u64,
+    offset_format: OffsetFormat,
+    on_edit: Option<Box<dyn Fn(Edit) -> Message + 'a>>,
+    on_cursor_move: Option<Box<dyn Fn(usize) -> Message + 'a>>,
+    on_selection: Option<Box<dyn Fn(Option<Range<usize>>) -> Message + 'a>>,
+    on_field: Option<Box<dyn Fn(Option<String>) -> Message + 'a>>,
+    on_focus: Option<Box<dyn Fn(bool) -> Message + 'a>>,
     message: PhantomData<Message>,
 }
 
 /// The local state of an [`Hexview`].
 ///
+/// Edits never reach the [`ByteSource`] the data is read from, they are kept
+/// in a [`PieceTable`] instead.
+///
 /// [`Hexview`]: struct.Hexview.html
+/// [`ByteSource`]: trait.ByteSource.html
+/// [`PieceTable`]: struct.PieceTable.html
 #[derive(Debug)]
 pub struct State {
-    bytes: Vec<u8>,
+    data: PieceTable,
     cursor: usize,
-    bytes_hash: u64,
     keyboard_focus: bool,
     test_offset: f32,
     debug_enabled: bool,
     last_click: Option<mouse::click::Click>,
     last_click_pos: Option<Point>,
     is_dragging: bool,
-    selection: Option<(usize, usize)>,
+    selection: Option<Selection>,
     mouse_pos: Point,
+    scroll: ScrollPosition,
+    scroller_grabbed_at: Option<f32>,
+    digit: usize,
+    insert_mode: bool,
+    history: History,
+    pane: Pane,
+    goto_input: Option<GotoInput>,
+    view: Option<View>,
+    cursor_scroll_pending: bool,
+    matches: Vec<Range<usize>>,
+    regions: Vec<Region>,
+    region_ids: Vec<RegionId>,
+    next_region_id: u64,
+    template: Option<Field>,
+    template_leaves: Vec<Range<usize>>,
+    differences: Arc<Vec<Range<usize>>>,
+    edited: Option<Range<usize>>,
+    revision: u64,
+    column_fit: Cell<Option<(ColumnFit, u8)>>,
+}
+
+/// A range of bytes in an [`Hexview`] marked with a color and a label, like
+/// a parsed structure.
+///
+/// Regions aren't moved by edits to the data.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    /// The marked bytes.
+    pub range: Range<usize>,
+    /// The background color of the marked bytes.
+    pub color: Color,
+    /// What's shown when the mouse is over the marked bytes.
+    pub label: String,
+}
+
+/// Identifies a [`Region`] added to a [`State`].
+///
+/// [`Region`]: struct.Region.html
+/// [`State`]: struct.State.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionId(u64);
+
+/// What a [`State`] knows about the last [`Hexview`] it was shown in, so it
+/// can scroll by itself.
+///
+/// [`State`]: struct.State.html
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy)]
+struct View {
+    column_count: usize,
+    row_height: f32,
+    visible_height: f32,
+}
+
+/// What the amount of columns fitted by an [`Hexview`] depends on, so it's
+/// only measured again when one of them changes.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ColumnFit {
+    width: f32,
+    font_size: f32,
+    multiple_of: ColumnMultiple,
+    radix: Radix,
+    group_size: u8,
+    offset_format: OffsetFormat,
+    base_address: u64,
+    len: usize,
+}
+
+/// How far the rows of an [`Hexview`] are scrolled.
+///
+/// The position is kept as the first visible row and how much of it is
+/// scrolled past, so data of any size can be scrolled through without
+/// losing precision.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrollPosition {
+    /// The topmost row that can be seen, even if partially.
+    pub row: usize,
+    /// The pixels of `row` scrolled past, less than a row.
+    pub remainder: f32,
+}
+
+impl ScrollPosition {
+    /// Creates the position `rows` rows down, which may have a fraction.
+    fn from_rows(rows: f64, row_height: f32) -> Self {
+        let rows = rows.max(0.0);
+
+        Self {
+            row: rows.floor() as usize,
+            remainder: (rows.fract() * row_height as f64) as f32,
+        }
+    }
+
+    /// Returns how many rows down the position is, with a fraction.
+    fn rows(&self, row_height: f32) -> f64 {
+        if row_height > 0.0 {
+            self.row as f64 + self.remainder as f64 / row_height as f64
+        } else {
+            self.row as f64
+        }
+    }
+}
+
+/// The input of the go-to overlay of an [`Hexview`], opened with Ctrl+G.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Default)]
+pub struct GotoInput {
+    /// What the user has typed, which is parsed by [`parse_offset`].
+    ///
+    /// [`parse_offset`]: fn.parse_offset.html
+    pub value: String,
+    /// Whether `value` couldn't be parsed.
+    pub is_invalid: bool,
+}
+
+/// The base bytes are shown in, in an [`Hexview`].
+///
+/// Every byte takes the same amount of digits, padded with zeros.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Radix {
+    /// Base 16, like `FF`.
+    Hexadecimal,
+    /// Base 8, like `377`.
+    Octal,
+    /// Base 10, like `255`.
+    Decimal,
+    /// Base 2, like `11111111`.
+    Binary,
+}
+
+impl Radix {
+    /// Returns the base of the [`Radix`].
+    ///
+    /// [`Radix`]: enum.Radix.html
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Hexadecimal => 16,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Binary => 2,
+        }
+    }
+
+    /// Returns how many digits a byte takes in the [`Radix`].
+    ///
+    /// [`Radix`]: enum.Radix.html
+    pub fn digits(self) -> usize {
+        match self {
+            Radix::Hexadecimal => 2,
+            Radix::Octal | Radix::Decimal => 3,
+            Radix::Binary => 8,
+        }
+    }
+
+    /// Appends the digits of `byte` to `buffer`.
+    pub fn write_byte(self, byte: u8, buffer: &mut String) {
+        let base = self.base();
+
+        for position in (0..self.digits() as u32).rev() {
+            let digit = (byte as u32 / base.pow(position)) % base;
+
+            buffer.push(std::char::from_digit(digit, base).unwrap().to_ascii_uppercase());
+        }
+    }
+
+    /// Replaces the digit of `byte` at `position`, counting from the most
+    /// significant one, returning `None` if the result isn't a byte.
+    pub fn replace_digit(self, byte: u8, position: usize, digit: u8) -> Option<u8> {
+        let base = self.base();
+        let weight = base.pow((self.digits() - 1 - position) as u32);
+        let old = (byte as u32 / weight) % base;
+
+        let value = byte as u32 - old * weight + digit as u32 * weight;
+
+        if value <= u8::MAX as u32 && (digit as u32) < base {
+            Some(value as u8)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Radix {
+    fn default() -> Self {
+        Radix::Hexadecimal
+    }
+}
+
+/// How the offset of each row is shown in an [`Hexview`].
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OffsetFormat {
+    /// Hexadecimal digits, like `0001F4A0`.
+    Hexadecimal,
+    /// Decimal digits, like `00128160`.
+    Decimal,
+    /// Octal digits, like `00372240`.
+    Octal,
+    /// An x86 real-mode `segment:offset` pair in hexadecimal, like
+    /// `07C0:0010`, relative to the given segment.
+    ///
+    /// The segment goes up by `1000` whenever the offset goes past `FFFF`.
+    /// Addresses before the segment are shown normalized instead, with an
+    /// offset below `10`.
+    Segmented(u16),
+}
+
+impl OffsetFormat {
+    /// The least amount of digits an offset is padded to.
+    const MIN_DIGITS: usize = 8;
+
+    /// Returns how many digits offsets up to `max_address` need, which is
+    /// never less than 8.
+    pub fn digits(self, max_address: u64) -> usize {
+        let base = match self {
+            OffsetFormat::Hexadecimal | OffsetFormat::Segmented(_) => 16,
+            OffsetFormat::Decimal => 10,
+            OffsetFormat::Octal => 8,
+        };
+        let mut digits = 1;
+        let mut rest = max_address / base;
+
+        while rest > 0 {
+            digits += 1;
+            rest /= base;
+        }
+
+        digits.max(Self::MIN_DIGITS)
+    }
+
+    /// Formats `address`, padding it with zeros to `digits`.
+    ///
+    /// `digits` is ignored by [`Segmented`] offsets, which always have four
+    /// digits on each side.
+    ///
+    /// [`Segmented`]: #variant.Segmented
+    pub fn format(self, address: u64, digits: usize) -> String {
+        match self {
+            OffsetFormat::Hexadecimal => format!("{:01$X}", address, digits),
+            OffsetFormat::Decimal => format!("{:01$}", address, digits),
+            OffsetFormat::Octal => format!("{:01$o}", address, digits),
+            OffsetFormat::Segmented(segment) => {
+                let start = segment as u64 * 16;
+
+                let (segment, offset) = match address.checked_sub(start) {
+                    Some(relative) => (segment as u64 + (relative >> 16) * 0x1000, relative & 0xFFFF),
+                    None => (address >> 4, address & 0xF),
+                };
+
+                format!("{:04X}:{:04X}", segment, offset)
+            }
+        }
+    }
+}
+
+impl Default for OffsetFormat {
+    fn default() -> Self {
+        OffsetFormat::Hexadecimal
+    }
+}
+
+/// How the amount of columns of an [`Hexview`] is chosen.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Columns {
+    /// A fixed amount of columns, from 1 to [`MAX_COLUMNS`].
+    ///
+    /// [`MAX_COLUMNS`]: constant.MAX_COLUMNS.html
+    Fixed(u8),
+    /// As many columns as fit in the width of the widget with its fonts,
+    /// up to [`MAX_COLUMNS`].
+    ///
+    /// [`MAX_COLUMNS`]: constant.MAX_COLUMNS.html
+    Fit {
+        /// What the amount of columns has to be.
+        multiple_of: ColumnMultiple,
+    },
+}
+
+/// The amounts of columns [`Columns::Fit`] can choose from.
+///
+/// [`Columns::Fit`]: enum.Columns.html#variant.Fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnMultiple {
+    /// Any amount of columns.
+    One,
+    /// A power of two, like 8, 16 or 32.
+    PowerOfTwo,
+    /// A multiple of the group size, so rows have only whole groups.
+    GroupSize,
+}
+
+/// One of the two representations of the data in an [`Hexview`].
+///
+/// The cursor is in both panes at once, but only the active one gets what's
+/// typed. The user can switch between them with Tab.
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    /// The bytes, as hex pairs.
+    Bytes,
+    /// The text representation of the bytes.
+    Text,
+}
+
+/// The scrollbar of an [`Hexview`].
+///
+/// [`Hexview`]: struct.Hexview.html
+#[derive(Debug, Clone, Copy)]
+pub struct Scrollbar {
+    /// The bounds of the whole scrollbar.
+    pub bounds: Rectangle,
+    /// The bounds of the scroller, the part that can be dragged.
+    pub scroller: Rectangle,
 }
 
 /// The renderer of an `Hexview`.
@@ -58,17 +438,40 @@ pub trait Renderer: iced_native::Renderer {
     /// The style supported by this renderer.
     type Style: Default;
 
-    /// Calculates an offset to the data from mouse position.
+    /// Calculates an offset to the data from mouse position, with the rows
+    /// scrolled to `scroll`, along with the pane it's in.
     fn cursor_offset(
         &self,
         bounds: Rectangle,
         cursor_position: Point,
+        scroll: ScrollPosition,
         font: Font,
         size: f32,
         column_count: usize,
+        radix: Radix,
+        group_size: usize,
+        endianness: Endianness,
+        offset_format: OffsetFormat,
+        base_address: u64,
         extend_line: bool,
-        bytes: &[u8],
-    ) -> Option<usize>;
+        bytes_len: usize,
+    ) -> Option<(usize, Pane)>;
+
+    /// Measures how wide an `Hexview` has to be to show rows of
+    /// `column_count` bytes, from its left edge to the right of its
+    /// scrollbar.
+    fn width(
+        &self,
+        font: Font,
+        size: f32,
+        column_count: usize,
+        radix: Radix,
+        group_size: usize,
+        endianness: Endianness,
+        offset_format: OffsetFormat,
+        base_address: u64,
+        bytes_len: usize,
+    ) -> f32;
 
     /// Measures the text contents with the given size and font, returning the
     /// size of a laid out paragraph that fits in the provided bounds.
@@ -82,11 +485,23 @@ pub trait Renderer: iced_native::Renderer {
 
     /// Draws an `Hexview`.
     ///
+    /// Only the rows intersecting `viewport` need to be drawn. Regions are
+    /// layered in order over the sorted `template_fields`, and `hover_label`
+    /// is shown next to the mouse when it's over one of them. `differences`
+    /// and `matches` are sorted too. Bytes are shown in `radix`, in groups of
+    /// `group_size` ordered by `endianness`, and row offsets are shown in
+    /// `offset_format`, counting from `base_address`. The `digit` of the
+    /// cursor being typed is only set when the data can be edited, and the
+    /// cursor may be right past the end of the data when `insert_mode` is
+    /// on. The text pane is decoded with `encoding`. The cursor is drawn in
+    /// the active `pane`, with a ghost of it in the other one.
+    ///
     /// [`Hexview`]: struct.Hexview.html
     fn draw(
         &mut self,
         bounds: Rectangle,
         cursor_position: Point,
+        viewport: &Rectangle,
         style: &Self::Style,
         text_size: f32,
         column_count: usize,
@@ -96,8 +511,25 @@ pub trait Renderer: iced_native::Renderer {
         debug_enabled: bool,
         header_font: Font,
         data_font: Font,
-        selection: &Option<(usize, usize)>,
-        data: &[u8],
+        selection: Option<Range<usize>>,
+        radix: Radix,
+        group_size: usize,
+        endianness: Endianness,
+        offset_format: OffsetFormat,
+        base_address: u64,
+        digit: Option<usize>,
+        encoding: &dyn Encoding,
+        pane: Pane,
+        insert_mode: bool,
+        scroll: ScrollPosition,
+        scrollbar: Option<Scrollbar>,
+        goto_input: Option<&GotoInput>,
+        differences: &[Range<usize>],
+        matches: &[Range<usize>],
+        regions: &[Region],
+        template_fields: &[Range<usize>],
+        hover_label: Option<&str>,
+        source: &dyn ByteSource,
     ) -> Self::Output;
 }
 
@@ -110,7 +542,23 @@ impl<'a, Message, Renderer: self::Renderer> Hexview<'a, Message, Renderer> {
             font_size: 17.0,
             header_font: Font::Default,
             data_font: Font::Default,
-            column_count: 16,
+            columns: Columns::Fixed(16),
+            column_count: Cell::new(16),
+            height: Length::Fill,
+            read_only: true,
+            goto_overlay: true,
+            display_radix: Radix::default(),
+            group_size: 1,
+            endianness: Endianness::Big,
+            snap_to_groups: false,
+            encoding: Box::new(encoding::Builtin::Ascii),
+            base_address: 0,
+            offset_format: OffsetFormat::default(),
+            on_edit: None,
+            on_cursor_move: None,
+            on_selection: None,
+            on_field: None,
+            on_focus: None,
             message: PhantomData,
         }
     }
@@ -149,147 +597,795 @@ impl<'a, Message, Renderer: self::Renderer> Hexview<'a, Message, Renderer> {
 
     /// Sets the amount of columns in an [`Hexview`].
     ///
-    /// `count` will be clamped to a number in the range `1..=32`.
+    /// `count` will be clamped to a number in the range `1..=64`.
     ///
     /// [`Hexview`]: struct.Heview.html
-    pub fn column_count(mut self, count: u8) -> Self {
-        self.column_count = clamp(count, 1, 32);
-        self
+    pub fn column_count(self, count: u8) -> Self {
+        self.columns(Columns::Fixed(count))
     }
-}
 
-impl State {
-    /// Creates a new [`Hexview`] state with default values.
+    /// Sets how the amount of [`Columns`] in an [`Hexview`] is chosen.
     ///
+    /// [`Columns`]: enum.Columns.html
     /// [`Hexview`]: struct.Heview.html
-    pub fn new() -> Self {
-        Self {
-            bytes: Vec::new(),
-            cursor: 0,
-            bytes_hash: 0,
-            keyboard_focus: false,
-            test_offset: 0.0,
-            debug_enabled: false,
-            last_click: None,
-            last_click_pos: None,
-            is_dragging: false,
-            selection: None,
-            mouse_pos: Point::new(0.0, 0.0),
+    pub fn columns(mut self, columns: Columns) -> Self {
+        self.columns = match columns {
+            Columns::Fixed(count) => Columns::Fixed(clamp(count, 1, MAX_COLUMNS)),
+            fit => fit,
+        };
+
+        if let Columns::Fixed(count) = self.columns {
+            self.column_count.set(count);
         }
+
+        self
     }
 
-    /// Sets the data [`Hexview`] will be working with.
+    /// Sets the height of an [`Hexview`].
     ///
-    /// Currently, we just clone the data into a Vec, which should work fine for
-    /// small amounts of data.
+    /// Any height other than `Length::Shrink` will make the widget scroll its
+    /// rows by itself when they don't fit.
     ///
     /// [`Hexview`]: struct.Heview.html
-    pub fn set_bytes(&mut self, bytes: &[u8]) {
-        use std::hash::Hasher;
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
 
-        let mut hasher = iced_native::Hasher::default();
-        hasher.write(bytes);
-        self.bytes_hash = hasher.finish();
-        self.bytes = bytes.to_vec();
-        self.cursor = 0;
-        self.selection = None;
+    /// Sets whether the data in an [`Hexview`] can't be edited. It's
+    /// read-only by default.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
     }
 
-    /// Sets the keyboard focus of an [`Hexview`].
+    /// Sets the [`Radix`] bytes are shown and typed in, in an [`Hexview`].
     ///
-    /// The keyboard focus is automatically determined by whether the user has
-    /// cicked inside the widget, but can be manually set in order to use
-    /// shortcuts and move around.
+    /// It's hexadecimal by default.
     ///
+    /// [`Radix`]: enum.Radix.html
     /// [`Hexview`]: struct.Heview.html
-    pub fn set_keyboard_focus(&mut self, focus: bool) {
-        self.keyboard_focus = focus;
+    pub fn display_radix(mut self, radix: Radix) -> Self {
+        self.display_radix = radix;
+        self
     }
-}
 
-impl<'a, Message, Renderer> Widget<Message, Renderer> for Hexview<'a, Message, Renderer>
-where
-    Renderer: self::Renderer
-{
-    fn width(&self) -> Length {
-        Length::Fill
+    /// Sets how many bytes are shown together as a word in an [`Hexview`].
+    ///
+    /// `size` will be rounded up to 1, 2, 4 or 8. Groups are spaced apart,
+    /// and the bytes in them are shown in the order of the [`endianness`].
+    /// It's 1 by default.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`endianness`]: #method.endianness
+    pub fn group_size(mut self, size: u8) -> Self {
+        self.group_size = clamp(size, 1, 8).next_power_of_two();
+        self
     }
 
-    fn height(&self) -> Length {
-        Length::Fill
+    /// Sets the [`Endianness`] groups of bytes are shown in, in an
+    /// [`Hexview`].
+    ///
+    /// Little endian groups are shown byte-swapped, so they read as the
+    /// number they hold. It's big endian by default, which keeps the bytes
+    /// in the order they are in the data.
+    ///
+    /// [`Endianness`]: template/enum.Endianness.html
+    /// [`Hexview`]: struct.Heview.html
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
     }
 
-    fn layout(
-        &self,
-        _renderer: &Renderer,
-        limits: &layout::Limits,
-    ) -> layout::Node {
-        let limits = limits.width(Length::Fill);
-        let max_width = limits.max().width;
-        let rows = (self.state.bytes.len() as f32 / self.column_count as usize as usize as f32).ceil();
-        let rows_size = (self.font_size + LINE_SPACING) * rows;
+    /// Sets whether the cursor and selection of an [`Hexview`] move a whole
+    /// group of bytes at a time.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn snap_to_groups(mut self, snap: bool) -> Self {
+        self.snap_to_groups = snap;
+        self
+    }
 
-        // Vertical margins + top headers + rows
-        let height = MARGINS.y * 2.0 + self.font_size + LINE_SPACING + rows_size;
+    /// Sets the [`Encoding`] of the text pane of an [`Hexview`].
+    ///
+    /// Characters are shown on their first byte, and typed characters are
+    /// encoded with it. It's ASCII by default, and [`encoding::Builtin`] has
+    /// a few others.
+    ///
+    /// [`Encoding`]: encoding/trait.Encoding.html
+    /// [`Hexview`]: struct.Heview.html
+    /// [`encoding::Builtin`]: encoding/enum.Builtin.html
+    pub fn encoding(mut self, encoding: impl Encoding + 'a) -> Self {
+        self.encoding = Box::new(encoding);
+        self
+    }
 
-        layout::Node::new(Size::new(max_width, height))
+    /// Sets the address of the first byte in an [`Hexview`], which the
+    /// offset column counts from.
+    ///
+    /// Only the offsets shown change, everything else still counts from the
+    /// start of the data. It's 0 by default.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn base_address(mut self, address: u64) -> Self {
+        self.base_address = address;
+        self
     }
 
-    fn on_event(
-        &mut self,
-        event: Event,
-        layout: Layout<'_>,
-        cursor_position: Point,
-        _messages: &mut Vec<Message>,
-        renderer: &Renderer,
-        _clipboard: Option<&dyn Clipboard>,
-    ) {
-        use keyboard::{Event as KeyboardEvent, KeyCode};
-        use mouse::{Button as MouseButton, Event as MouseEvent};
+    /// Sets the [`OffsetFormat`] of the offset column of an [`Hexview`].
+    ///
+    /// The column is as wide as the offset of the last row needs. It's
+    /// hexadecimal by default.
+    ///
+    /// [`OffsetFormat`]: enum.OffsetFormat.html
+    /// [`Hexview`]: struct.Heview.html
+    pub fn offset_format(mut self, format: OffsetFormat) -> Self {
+        self.offset_format = format;
+        self
+    }
 
-        let bytes_len = self.state.bytes.len();
-        let column_count = self.column_count as usize;
-        let cursor = self.state.cursor;
-        let keyboard_focus = self.state.keyboard_focus;
-        let test_offset = self.state.test_offset;
-        let debug_enabled = self.state.debug_enabled;
-        let _last_click_pos = self.state.last_click_pos;
+    /// Sets whether Ctrl+G opens an overlay in an [`Hexview`] to type an
+    /// offset to go to.
+    ///
+    /// It's enabled by default. Offsets are parsed by [`parse_offset`], so
+    /// they can be relative or have simple arithmetic.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`parse_offset`]: fn.parse_offset.html
+    pub fn goto_overlay(mut self, enabled: bool) -> Self {
+        self.goto_overlay = enabled;
+        self
+    }
 
-        match event {
-            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
-                if !layout.bounds().contains(cursor_position) {
-                    return;
-                }
+    /// Sets the message that should be produced when the user edits the data
+    /// in an [`Hexview`].
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn on_edit<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Edit) -> Message,
+    {
+        self.on_edit = Some(Box::new(f));
+        self
+    }
 
-                self.state.is_dragging = true;
+    /// Sets the message that should be produced when the cursor of an
+    /// [`Hexview`] moves, with its new offset.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn on_cursor_move<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(usize) -> Message,
+    {
+        self.on_cursor_move = Some(Box::new(f));
+        self
+    }
 
-                let cursor_from_pos = renderer.cursor_offset(
-                    layout.bounds(),
-                    cursor_position,
-                    self.data_font,
-                    self.font_size,
-                    column_count as usize as usize,
-                    false,
-                    &self.state.bytes,
-                );
-                println!("Cursor from pos: {:?}", cursor_from_pos);
+    /// Sets the message that should be produced when the selection of an
+    /// [`Hexview`] changes, with the selected range if there's one.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn on_selection<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Option<Range<usize>>) -> Message,
+    {
+        self.on_selection = Some(Box::new(f));
+        self
+    }
 
-                if let Some(cursor) = cursor_from_pos {
-                    self.state.cursor = cursor;
-                }
+    /// Sets the message that should be produced when the cursor of an
+    /// [`Hexview`] moves to another template field, with the path of that
+    /// field if there's one.
+    ///
+    /// The field itself can be found with [`State::field_at_cursor`].
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`State::field_at_cursor`]: struct.State.html#method.field_at_cursor
+    pub fn on_field<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Option<String>) -> Message,
+    {
+        self.on_field = Some(Box::new(f));
+        self
+    }
 
-                self.state.selection = None;
-                self.state.last_click_pos = Some(cursor_position);
+    /// Sets the message that should be produced when an [`Hexview`] gains or
+    /// loses keyboard focus.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn on_focus<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(bool) -> Message,
+    {
+        self.on_focus = Some(Box::new(f));
+        self
+    }
 
-                let click = mouse::Click::new(
-                    cursor_position,
-                    self.state.last_click,
-                );
+    fn is_editable(&self) -> bool {
+        !self.read_only
+    }
+
+    /// The furthest the cursor can go, which is past the last byte when
+    /// inserting.
+    fn last_cursor(&self) -> usize {
+        let len = self.state.data.len();
+
+        if self.state.insert_mode && self.is_editable() {
+            len
+        } else {
+            len.saturating_sub(1)
+        }
+    }
+
+    /// Replaces the bytes in `range`, producing an edit message.
+    ///
+    /// `merge` joins the edit with the previous one in the history, which is
+    /// what typing does.
+    fn replace(
+        &mut self,
+        range: Range<usize>,
+        bytes: &[u8],
+        merge: bool,
+        messages: &mut Vec<Message>,
+    ) {
+        let edit = self.state.apply(range, bytes, merge);
+        self.publish_edits(vec![edit], messages);
+    }
+
+    fn publish_edits(&self, edits: Vec<Edit>, messages: &mut Vec<Message>) {
+        if let Some(on_edit) = &self.on_edit {
+            messages.extend(edits.into_iter().map(on_edit));
+        }
+    }
+
+    /// Writes a digit of the display radix at the cursor, moving to the next
+    /// digit.
+    ///
+    /// In insert mode, typing the first digit inserts a new byte. Digits that
+    /// would make the byte too big, like `3` first in decimal, are ignored.
+    fn type_digit(&mut self, digit: u8, messages: &mut Vec<Message>) {
+        let offset = self.state.cursor;
+        let position = self.state.digit;
+        let inserting = self.state.insert_mode && position == 0;
+        let mut old = [0];
+
+        if !inserting && self.state.data.read(offset, &mut old) == 0 {
+            return;
+        }
+
+        // When not every combination of digits is a byte, like in decimal,
+        // the first digit typed clears the ones after it, so typing 2 over
+        // 199 gives 200 instead of an invalid 299
+        let radix = self.display_radix;
+        let old = if position == 0 && radix.base().pow(radix.digits() as u32) > 256 {
+            0
+        } else {
+            old[0]
+        };
+
+        let new = match radix.replace_digit(old, position, digit) {
+            Some(new) => new,
+            None => return,
+        };
+
+        if inserting {
+            self.replace(offset..offset, &[new], true, messages);
+        } else {
+            self.replace(offset..(offset + 1), &[new], true, messages);
+        }
+
+        if position + 1 < radix.digits() {
+            self.state.digit += 1;
+        } else {
+            self.state.digit = 0;
+            self.state.cursor = (offset + 1).min(self.last_cursor());
+        }
+    }
+
+    /// Writes `bytes` at the cursor, moving right past them.
+    ///
+    /// In insert mode, the bytes are inserted instead. Otherwise they only
+    /// overwrite the data that's there.
+    fn type_bytes(&mut self, bytes: &[u8], messages: &mut Vec<Message>) {
+        let offset = self.state.cursor;
+
+        if self.state.insert_mode {
+            self.replace(offset..offset, bytes, true, messages);
+        } else if offset + bytes.len() <= self.state.data.len() {
+            self.replace(offset..(offset + bytes.len()), bytes, true, messages);
+        } else {
+            return;
+        }
+
+        self.state.cursor = (offset + bytes.len()).min(self.last_cursor());
+    }
+
+    /// Deletes `range`, or the selection if there's one, moving the cursor to
+    /// where the deleted bytes were.
+    fn delete(&mut self, range: Option<Range<usize>>, messages: &mut Vec<Message>) {
+        let selection = self.state.selection.take().map(|selection| selection.range());
+
+        if let Some(range) = selection.or(range) {
+            self.state.cursor = range.start;
+            self.replace(range, &[], false, messages);
+        }
+
+        self.state.cursor = self.state.cursor.min(self.last_cursor());
+        self.state.digit = 0;
+    }
+
+    /// Calculates where a navigation key moves `from`, without going past
+    /// `last`.
+    ///
+    /// When `extend`ing a selection, End goes right past the end of the row
+    /// so the last byte in it can be selected. Pages are `page_rows` long,
+    /// and `control` is whether Ctrl is held.
+    fn navigate(
+        &self,
+        key_code: keyboard::KeyCode,
+        control: bool,
+        from: usize,
+        last: usize,
+        extend: bool,
+        page_rows: usize,
+    ) -> Option<usize> {
+        use keyboard::KeyCode;
+
+        let column_count = self.row_len();
+        let line_start = from / column_count * column_count;
+        let page = page_rows * column_count;
+        let step = if self.snap_to_groups { self.group_size as usize } else { 1 };
+
+        let to = match key_code {
+            KeyCode::Left => self.snap(from.checked_sub(1)?),
+            KeyCode::Right => self.snap(from) + step,
+            // Ctrl+Up and Ctrl+Down scroll instead
+            KeyCode::Up if !control => from.checked_sub(column_count)?,
+            KeyCode::Down if !control => from + column_count,
+            KeyCode::Home if control => 0,
+            KeyCode::End if control => last,
+            KeyCode::Home => line_start,
+            KeyCode::End if extend => line_start + column_count,
+            KeyCode::End => line_start + column_count - 1,
+            // The column is kept when there isn't a whole page to go up
+            KeyCode::PageUp => from.checked_sub(page).unwrap_or(from % column_count),
+            KeyCode::PageDown => from + page,
+            _ => return None,
+        };
+        let to = to.min(last);
+
+        // Selections are snapped on their own, and the last offset may be
+        // right past the end of the data
+        Some(if extend || to == last { to } else { self.snap(to) })
+    }
+
+    /// Moves `offset` to the start of its group when snapping to groups.
+    fn snap(&self, offset: usize) -> usize {
+        if self.snap_to_groups {
+            offset - offset % self.group_size as usize
+        } else {
+            offset
+        }
+    }
+
+    /// Creates a selection from `anchor` to `active`, growing it to whole
+    /// groups when snapping to groups.
+    fn snap_selection(&self, anchor: usize, active: usize) -> Selection {
+        let group_size = self.group_size as usize;
+
+        if !self.snap_to_groups || anchor == active {
+            Selection::new(anchor, active)
+        } else if anchor < active {
+            Selection::new(self.snap(anchor), self.snap(active + group_size - 1))
+        } else {
+            Selection::new(self.snap(anchor + group_size - 1), self.snap(active))
+        }
+    }
+
+    /// The amount of rows that fit in the area where rows are shown.
+    fn page_rows(&self, bounds: Rectangle) -> usize {
+        let rows = self.rows_bounds(bounds).height / self.row_height();
+
+        (rows.floor() as usize).max(1)
+    }
+
+    /// Scrolls the rows by `rows`, which may be negative, clamping it to
+    /// `max_rows`.
+    fn scroll_rows(&mut self, rows: f64, max_rows: f64) {
+        let row_height = self.row_height();
+        let target = self.state.scroll.rows(row_height) + rows;
+
+        self.state.scroll = ScrollPosition::from_rows(clamp(target, 0.0, max_rows), row_height);
+    }
+
+    /// The range of the value containing `offset`, which is its group of
+    /// bytes.
+    fn value_range(&self, offset: usize) -> Range<usize> {
+        let group_size = self.group_size as usize;
+        let len = self.state.data.len();
+        let start = offset - offset % group_size;
+
+        start.min(len)..(start + group_size).min(len)
+    }
+
+    /// The range of the row containing `offset`.
+    fn row_range(&self, offset: usize) -> Range<usize> {
+        let column_count = self.row_len();
+        let len = self.state.data.len();
+        let start = offset / column_count * column_count;
+
+        start.min(len)..(start + column_count).min(len)
+    }
+
+    fn row_height(&self) -> f32 {
+        self.font_size + LINE_SPACING
+    }
+
+    fn row_count(&self) -> usize {
+        // The cursor may be right past the end when inserting, which could be
+        // in a row of its own
+        let len = self.state.data.len().max(self.state.cursor + 1);
+
+        (len as f64 / self.row_len() as f64).ceil() as usize
+    }
+
+    /// The amount of columns in each row, as last chosen by
+    /// [`fit_columns`].
+    ///
+    /// [`fit_columns`]: #method.fit_columns
+    fn row_len(&self) -> usize {
+        self.column_count.get() as usize
+    }
+
+    /// Chooses the amount of columns for a widget `width` wide, which the
+    /// other methods use from then on.
+    ///
+    /// With [`Columns::Fit`], it's the most columns the renderer can fit,
+    /// or the fewest allowed if not even those fit. The result is kept in
+    /// the [`State`] until something it depends on changes.
+    ///
+    /// [`Columns::Fit`]: enum.Columns.html#variant.Fit
+    /// [`State`]: struct.State.html
+    fn fit_columns(&self, renderer: &Renderer, width: f32) {
+        let multiple_of = match self.columns {
+            Columns::Fixed(count) => {
+                self.column_count.set(count);
+                return;
+            }
+            Columns::Fit { multiple_of } => multiple_of,
+        };
+        let fit = ColumnFit {
+            width,
+            font_size: self.font_size,
+            multiple_of,
+            radix: self.display_radix,
+            group_size: self.group_size,
+            offset_format: self.offset_format,
+            base_address: self.base_address,
+            len: self.state.data.len(),
+        };
+
+        if let Some((last_fit, count)) = self.state.column_fit.get() {
+            if last_fit == fit {
+                self.column_count.set(count);
+                return;
+            }
+        }
+
+        let group_size = self.group_size;
+        let mut candidates = (1..=MAX_COLUMNS).filter(|count| match multiple_of {
+            ColumnMultiple::One => true,
+            ColumnMultiple::PowerOfTwo => count.is_power_of_two(),
+            ColumnMultiple::GroupSize => count % group_size == 0,
+        });
+        let mut count = candidates.next().unwrap_or(1);
+
+        for candidate in candidates {
+            let needed = renderer.width(
+                self.header_font,
+                self.font_size,
+                candidate as usize,
+                self.display_radix,
+                self.group_size as usize,
+                self.endianness,
+                self.offset_format,
+                self.base_address,
+                self.state.data.len(),
+            );
+
+            if needed > width {
+                break;
+            }
+
+            count = candidate;
+        }
+
+        self.column_count.set(count);
+        self.state.column_fit.set(Some((fit, count)));
+    }
+
+    /// The area below the column header where rows are shown.
+    fn rows_bounds(&self, bounds: Rectangle) -> Rectangle {
+        let header_height = MARGINS.y + self.row_height();
+
+        Rectangle {
+            x: bounds.x,
+            y: bounds.y + header_height,
+            width: bounds.width,
+            height: (bounds.height - header_height - MARGINS.y).max(0.0),
+        }
+    }
+
+    /// The amount of rows that can be scrolled past, with a fraction.
+    fn max_scroll_rows(&self, bounds: Rectangle) -> f64 {
+        let visible_rows = self.rows_bounds(bounds).height as f64 / self.row_height() as f64;
+
+        (self.row_count() as f64 - visible_rows).max(0.0)
+    }
+
+    /// The scroll position of the state, clamped to what fits in `bounds`.
+    ///
+    /// If the state was asked to scroll to the cursor before being shown,
+    /// it's done here, so it can be drawn right away.
+    fn scroll_position(&self, bounds: Rectangle) -> ScrollPosition {
+        if self.state.cursor_scroll_pending {
+            return self.state.cursor_scroll(self.view(bounds));
+        }
+
+        let max_rows = self.max_scroll_rows(bounds);
+        let row_height = self.row_height();
+
+        if self.state.scroll.rows(row_height) > max_rows {
+            ScrollPosition::from_rows(max_rows, row_height)
+        } else {
+            self.state.scroll
+        }
+    }
+
+    fn scrollbar(&self, bounds: Rectangle) -> Option<Scrollbar> {
+        let max_rows = self.max_scroll_rows(bounds);
+
+        if max_rows <= 0.0 {
+            return None;
+        }
+
+        let rows_bounds = self.rows_bounds(bounds);
+        let track = Rectangle {
+            x: bounds.x + bounds.width - SCROLLBAR_WIDTH,
+            y: rows_bounds.y,
+            width: SCROLLBAR_WIDTH,
+            height: rows_bounds.height,
+        };
+        let visible_rows = rows_bounds.height as f64 / self.row_height() as f64;
+        let ratio = (visible_rows / (visible_rows + max_rows)) as f32;
+        let scroller_height = (track.height * ratio).max(MIN_SCROLLER_HEIGHT).min(track.height);
+        let scroll_ratio = (self.scroll_position(bounds).rows(self.row_height()) / max_rows) as f32;
+
+        Some(Scrollbar {
+            bounds: track,
+            scroller: Rectangle {
+                y: track.y + (track.height - scroller_height) * scroll_ratio,
+                height: scroller_height,
+                ..track
+            },
+        })
+    }
+
+    /// The label of the topmost region or template field under the mouse, if
+    /// it's over the rows.
+    fn hover_label(
+        &self,
+        renderer: &Renderer,
+        bounds: Rectangle,
+        cursor_position: Point,
+    ) -> Option<String> {
+        if (self.state.regions.is_empty() && self.state.template.is_none())
+            || !self.rows_bounds(bounds).contains(cursor_position)
+        {
+            return None;
+        }
+
+        let (offset, _) = renderer.cursor_offset(
+            bounds,
+            cursor_position,
+            self.scroll_position(bounds),
+            self.data_font,
+            self.font_size,
+            self.row_len(),
+            self.display_radix,
+            self.group_size as usize,
+            self.endianness,
+            self.offset_format,
+            self.base_address,
+            false,
+            self.state.data.len(),
+        )?;
+
+        match self.state.regions.iter().rev().find(|region| region.range.contains(&offset)) {
+            Some(region) => Some(region.label.clone()),
+            None => self
+                .state
+                .template
+                .as_ref()?
+                .field_at(offset)
+                .map(|field| format!("{}: {}", field.path, field.value)),
+        }
+    }
+
+    fn view(&self, bounds: Rectangle) -> View {
+        View {
+            column_count: self.row_len(),
+            row_height: self.row_height(),
+            visible_height: self.rows_bounds(bounds).height,
+        }
+    }
+
+    /// Handles a key pressed while the go-to overlay is open, which takes
+    /// every key.
+    fn goto_key(&mut self, key_code: keyboard::KeyCode) {
+        use keyboard::KeyCode;
+
+        let cursor = self.state.cursor;
+        let input = match &mut self.state.goto_input {
+            Some(input) => input,
+            None => return,
+        };
+
+        match key_code {
+            KeyCode::Escape => self.state.goto_input = None,
+            KeyCode::Backspace => {
+                input.value.pop();
+                input.is_invalid = false;
+            }
+            KeyCode::Enter | KeyCode::NumpadEnter => match parse_offset(&input.value, cursor) {
+                Ok(offset) => {
+                    self.state.goto_input = None;
+                    self.state.goto(offset);
+                }
+                Err(_) => input.is_invalid = true,
+            },
+            _ => (),
+        }
+    }
+
+    /// Updates the state of an [`Hexview`] with an event from [`on_event`].
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`on_event`]: #method.on_event
+    fn handle_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        renderer: &Renderer,
+    ) {
+        use keyboard::{Event as KeyboardEvent, KeyCode};
+        use mouse::{Button as MouseButton, Event as MouseEvent, ScrollDelta};
+
+        let bounds = layout.bounds();
+
+        self.fit_columns(renderer, bounds.width);
+
+        let max_scroll_rows = self.max_scroll_rows(bounds);
+        self.state.scroll = self.scroll_position(bounds);
+        self.state.cursor_scroll_pending = false;
+        self.state.view = Some(self.view(bounds));
+        let bytes_len = self.state.data.len();
+        let column_count = self.row_len();
+        let cursor = self.state.cursor;
+        let keyboard_focus = self.state.keyboard_focus;
+        let test_offset = self.state.test_offset;
+        let debug_enabled = self.state.debug_enabled;
+        let editable = self.is_editable();
+        let _last_click_pos = self.state.last_click_pos;
+
+        match event {
+            Event::Mouse(MouseEvent::WheelScrolled { delta }) => {
+                if !bounds.contains(cursor_position) {
+                    return;
+                }
+
+                let delta_y = match delta {
+                    ScrollDelta::Lines { y, .. } => y * self.row_height() * WHEEL_ROWS,
+                    ScrollDelta::Pixels { y, .. } => y,
+                };
+
+                self.scroll_rows(-(delta_y / self.row_height()) as f64, max_scroll_rows);
+            }
+
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                if !bounds.contains(cursor_position) {
+                    return;
+                }
+
+                if let Some(scrollbar) = self.scrollbar(bounds) {
+                    if scrollbar.bounds.contains(cursor_position) {
+                        // Clicking outside of the scroller jumps to that point
+                        let grabbed_at = if scrollbar.scroller.contains(cursor_position) {
+                            cursor_position.y - scrollbar.scroller.y
+                        } else {
+                            scrollbar.scroller.height / 2.0
+                        };
+
+                        self.state.scroller_grabbed_at = Some(grabbed_at);
+                        self.state.scroll = ScrollPosition::from_rows(
+                            scrollbar.scroll_rows(cursor_position.y - grabbed_at, max_scroll_rows),
+                            self.row_height(),
+                        );
+                        return;
+                    }
+                }
+
+                if !self.rows_bounds(bounds).contains(cursor_position) {
+                    return;
+                }
+
+                self.state.is_dragging = true;
+
+                let cursor_from_pos = renderer.cursor_offset(
+                    bounds,
+                    cursor_position,
+                    self.state.scroll,
+                    self.data_font,
+                    self.font_size,
+                    column_count as usize as usize,
+                    self.display_radix,
+                    self.group_size as usize,
+                    self.endianness,
+                    self.offset_format,
+                    self.base_address,
+                    false,
+                    bytes_len,
+                );
+
+                if let Some((cursor, pane)) = cursor_from_pos {
+                    self.state.cursor = self.snap(cursor.min(self.last_cursor()));
+                    self.state.pane = pane;
+                    self.state.digit = 0;
+                }
+
+                self.state.history.close_group();
+
+                self.state.selection = None;
+                self.state.last_click_pos = Some(cursor_position);
+
+                let click = mouse::Click::new(
+                    cursor_position,
+                    self.state.last_click,
+                );
+
+                // Double and triple clicks select a whole thing at once, so
+                // there's nothing left to drag
+                let range = match (click.kind(), cursor_from_pos) {
+                    (mouse::click::Kind::Double, Some((offset, Pane::Bytes))) => {
+                        Some(self.value_range(offset))
+                    }
+                    (mouse::click::Kind::Double, Some((offset, Pane::Text))) => {
+                        Some(printable_run(&self.state.data, self.encoding.as_ref(), offset))
+                    }
+                    (mouse::click::Kind::Triple, Some((offset, _))) => {
+                        Some(self.row_range(offset))
+                    }
+                    _ => None,
+                };
+
+                if let Some(range) = range {
+                    self.state.set_selection(Some(Selection::new(range.start, range.end)));
+                    self.state.is_dragging = false;
+                    self.state.last_click_pos = None;
+                }
 
                 self.state.last_click = Some(click);
             }
 
             Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) => {
+                if self.state.scroller_grabbed_at.take().is_some() {
+                    return;
+                }
+
                 if let Some(pos) = self.state.last_click_pos.take() {
                     if cursor_position == pos {
                         self.state.selection = None;
@@ -298,111 +1394,1100 @@ where
 
                 self.state.is_dragging = false;
                 self.state.set_keyboard_focus(layout.bounds().contains(cursor_position));
+
+                if !self.state.keyboard_focus {
+                    self.state.goto_input = None;
+                }
             }
 
             Event::Mouse(MouseEvent::CursorMoved { .. }) => {
-                if self.state.is_dragging {
+                if let Some(grabbed_at) = self.state.scroller_grabbed_at {
+                    if let Some(scrollbar) = self.scrollbar(bounds) {
+                        self.state.scroll = ScrollPosition::from_rows(
+                            scrollbar.scroll_rows(cursor_position.y - grabbed_at, max_scroll_rows),
+                            self.row_height(),
+                        );
+                    }
+                } else if self.state.is_dragging {
                     let cursor_from_pos = renderer.cursor_offset(
-                        layout.bounds(),
+                        bounds,
                         cursor_position,
+                        self.state.scroll,
                         self.data_font,
                         self.font_size,
                         column_count as usize as usize,
+                        self.display_radix,
+                        self.group_size as usize,
+                        self.endianness,
+                        self.offset_format,
+                        self.base_address,
                         true,
-                        &self.state.bytes,
+                        bytes_len,
                     );
 
-                    if let Some(new_cursor) = cursor_from_pos {
-                        if new_cursor < cursor {
-                            self.state.selection = Some((new_cursor, cursor))
-                        } else if new_cursor > cursor {
-                            self.state.selection = Some((cursor, new_cursor))
-                        } else {
-                            self.state.selection = None;
-                        }
-                    }
+                    if let Some((new_cursor, _)) = cursor_from_pos {
+                        let anchor = self.state.selection.map_or(cursor, |selection| selection.anchor);
 
-                    println!("Selection: {:?}", self.state.selection);
+                        self.state.set_selection(Some(self.snap_selection(anchor, new_cursor)));
+                    }
                 }
             }
 
-            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. }) => {
-                let line_start = cursor / column_count as usize as usize * column_count as usize;
-                let line_end = (line_start + column_count as usize - 1).min(bytes_len - 1);
-                let cursor_guard_left = cursor > 0 && keyboard_focus;
-                let cursor_guard_right = if bytes_len > 0 {
-                    self.state.cursor < bytes_len - 1 && keyboard_focus
-                } else {
-                    false
-                };
-                let cursor_guard_up = cursor >= column_count as usize && keyboard_focus;
-                let cursor_guard_down = bytes_len > 0 && keyboard_focus;
-                let cursor_guard_home = cursor > line_start && keyboard_focus;
-                let cursor_guard_end = cursor < line_end && keyboard_focus;
-                let cursor_guard_pageup = cursor > 0 && keyboard_focus;
-                let cursor_guard_pagedown = bytes_len > 0 && keyboard_focus;
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. })
+                if self.state.goto_input.is_some() =>
+            {
+                self.goto_key(key_code);
+            }
+
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, modifiers }) => {
+                let last_cursor = self.last_cursor();
+                let edit_guard = keyboard_focus && editable;
                 let test_offset_guard_left = test_offset > f32::MIN && debug_enabled;
                 let test_offset_guard_right = test_offset < f32::MAX && debug_enabled;
 
+                // Moving with Shift extends the selection, which starts at the
+                // cursor, and may go right past the last byte to include it
+                let extend = modifiers.shift;
+                let selection = self.state.selection;
+                let from = match selection {
+                    Some(selection) if extend => selection.active,
+                    _ => cursor,
+                };
+                let last = if extend { bytes_len.max(last_cursor) } else { last_cursor };
+                let target = if keyboard_focus {
+                    self.navigate(key_code, modifiers.control, from, last, extend, self.page_rows(bounds))
+                } else {
+                    None
+                };
+
+                if let Some(to) = target {
+                    // Paging keeps the cursor in the same place of the view
+                    if let KeyCode::PageUp | KeyCode::PageDown = key_code {
+                        let rows = (to / column_count) as f64 - (from / column_count) as f64;
+                        self.scroll_rows(rows, max_scroll_rows);
+                    }
+
+                    if extend {
+                        let anchor = selection.map_or(cursor, |selection| selection.anchor);
+
+                        self.state.set_selection(Some(self.snap_selection(anchor, to)));
+                    } else {
+                        self.state.selection = None;
+                        self.state.cursor = to;
+                    }
+                }
+
                 match key_code {
                     // Cursor movement
-                    KeyCode::Left if cursor_guard_left => self.state.cursor -= 1,
-                    KeyCode::Right if cursor_guard_right => self.state.cursor += 1,
-                    KeyCode::Up if cursor_guard_up => self.state.cursor -= column_count as usize,
-                    KeyCode::Down if cursor_guard_down => {
-                        if cursor + column_count as usize <= bytes_len - 1 {
-                            self.state.cursor += column_count as usize;
-                        } else {
-                            self.state.cursor = bytes_len - 1;
-                        }
-                    },
-                    KeyCode::Home if cursor_guard_home => self.state.cursor = line_start,
-                    KeyCode::End if cursor_guard_end => self.state.cursor = line_end,
-                    // TODO: Calculate pages based on visible lines
-                    KeyCode::PageUp if cursor_guard_pageup => self.state.cursor = 0,
-                    KeyCode::PageDown if cursor_guard_pagedown => {
-                        if bytes_len > 0 {
-                            self.state.cursor = bytes_len - 1;
-                        }
-                    },
+                    _ if target.is_some() => (),
+
+                    // Scrolling
+                    KeyCode::Up if keyboard_focus && modifiers.control => {
+                        self.scroll_rows(-1.0, max_scroll_rows);
+                    }
+                    KeyCode::Down if keyboard_focus && modifiers.control => {
+                        self.scroll_rows(1.0, max_scroll_rows);
+                    }
+
+                    // Selection
+                    KeyCode::A if keyboard_focus && modifiers.control => self.state.select_all(),
+                    KeyCode::Escape if keyboard_focus => self.state.selection = None,
+
+                    // Search
+                    KeyCode::F3 if keyboard_focus && modifiers.shift => {
+                        self.state.previous_match();
+                    }
+                    KeyCode::F3 if keyboard_focus => {
+                        self.state.next_match();
+                    }
+
+                    // Go to
+                    KeyCode::G if keyboard_focus && modifiers.control && self.goto_overlay => {
+                        self.state.goto_input = Some(GotoInput::default());
+                    }
+
+                    // Panes
+                    KeyCode::Tab if keyboard_focus => {
+                        self.state.set_pane(match self.state.pane {
+                            Pane::Bytes => Pane::Text,
+                            Pane::Text => Pane::Bytes,
+                        });
+                    }
+
+                    // Editing
+                    KeyCode::Insert if edit_guard => {
+                        self.state.set_insert_mode(!self.state.insert_mode);
+                        self.state.digit = 0;
+                    }
+                    KeyCode::Backspace if edit_guard => {
+                        let previous = if cursor > 0 { Some((cursor - 1)..cursor) } else { None };
+                        self.delete(previous, messages);
+                    }
+                    KeyCode::Delete if edit_guard => {
+                        let current = if cursor < bytes_len { Some(cursor..(cursor + 1)) } else { None };
+                        self.delete(current, messages);
+                    }
+                    KeyCode::Z if edit_guard && modifiers.control && !modifiers.shift => {
+                        let edits = self.state.undo();
+                        self.publish_edits(edits, messages);
+                    }
+                    KeyCode::Y | KeyCode::Z if edit_guard && modifiers.control => {
+                        let edits = self.state.redo();
+                        self.publish_edits(edits, messages);
+                    }
 
                     // Test offset
                     KeyCode::Minus if test_offset_guard_left => self.state.test_offset -= 0.01,
                     KeyCode::Equals if test_offset_guard_right => self.state.test_offset += 0.01,
 
                     // Debug
-                    KeyCode::D if keyboard_focus => self.state.debug_enabled = !debug_enabled,
+                    // D is a hex digit when editing
+                    KeyCode::D if keyboard_focus && !editable => {
+                        self.state.debug_enabled = !debug_enabled
+                    }
 
                     _ => (),
                 }
+
+                if self.state.cursor != cursor {
+                    self.state.digit = 0;
+                    self.state.history.close_group();
+                    self.state.scroll_to_cursor();
+                }
+            }
+
+            Event::Keyboard(KeyboardEvent::CharacterReceived(c))
+                if self.state.goto_input.is_some() =>
+            {
+                if let Some(input) = &mut self.state.goto_input {
+                    if !c.is_control() {
+                        input.value.push(c);
+                        input.is_invalid = false;
+                    }
+                }
+            }
+
+            Event::Keyboard(KeyboardEvent::CharacterReceived(c)) => {
+                if !keyboard_focus || !editable {
+                    return;
+                }
+
+                match self.state.pane {
+                    Pane::Bytes => if let Some(digit) = c.to_digit(self.display_radix.base()) {
+                        self.type_digit(digit as u8, messages);
+                        self.state.scroll_to_cursor();
+                    },
+                    Pane::Text => if let Some(bytes) = self.encoding.encode(c).filter(|_| !c.is_control()) {
+                        self.type_bytes(&bytes, messages);
+                        self.state.scroll_to_cursor();
+                    },
+                }
             }
 
             _ => (),
         }
     }
+}
 
-    fn draw(
+impl State {
+    /// Creates a new [`Hexview`] state with default values.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn new() -> Self {
+        Self {
+            data: PieceTable::new(Vec::new()),
+            cursor: 0,
+            keyboard_focus: false,
+            test_offset: 0.0,
+            debug_enabled: false,
+            last_click: None,
+            last_click_pos: None,
+            is_dragging: false,
+            selection: None,
+            mouse_pos: Point::new(0.0, 0.0),
+            scroll: ScrollPosition::default(),
+            scroller_grabbed_at: None,
+            digit: 0,
+            insert_mode: false,
+            history: History::default(),
+            pane: Pane::Bytes,
+            goto_input: None,
+            view: None,
+            cursor_scroll_pending: false,
+            matches: Vec::new(),
+            regions: Vec::new(),
+            region_ids: Vec::new(),
+            next_region_id: 0,
+            template: None,
+            template_leaves: Vec::new(),
+            differences: Arc::new(Vec::new()),
+            edited: None,
+            revision: 0,
+            column_fit: Cell::new(None),
+        }
+    }
+
+    /// Sets the data [`Hexview`] will be working with.
+    ///
+    /// The data is cloned into a Vec, which should work fine for small
+    /// amounts of data. Use [`set_source`] with an `Arc<[u8]>` or a
+    /// `Cow<'static, [u8]>` to avoid the copy.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`set_source`]: #method.set_source
+    pub fn set_bytes(&mut self, bytes: &[u8]) {
+        self.set_source(bytes.to_vec());
+    }
+
+    /// Sets the [`ByteSource`] [`Hexview`] will be reading from.
+    ///
+    /// Any edits made to the previous data are discarded.
+    ///
+    /// [`ByteSource`]: trait.ByteSource.html
+    /// [`Hexview`]: struct.Heview.html
+    pub fn set_source(&mut self, source: impl ByteSource + 'static) {
+        self.data = PieceTable::new(source);
+        self.cursor = 0;
+        self.selection = None;
+        self.scroll = ScrollPosition::default();
+        self.cursor_scroll_pending = false;
+        self.digit = 0;
+        self.history.clear();
+        self.matches.clear();
+        self.clear_template();
+        self.edited = Some(0..usize::MAX);
+        self.revision += 1;
+    }
+
+    /// Opens the file at `path` as the data [`Hexview`] will be working with.
+    ///
+    /// The file is memory-mapped read-only through a [`FileSource`], so it
+    /// doesn't matter how big it is. Edits go to the [`PieceTable`] and never
+    /// reach the file.
+    ///
+    /// # Warning
+    ///
+    /// The file must not be truncated or modified, by this or any other
+    /// process, until another source replaces it. Reading a truncated mapping
+    /// raises `SIGBUS` and aborts the program.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`FileSource`]: struct.FileSource.html
+    /// [`PieceTable`]: struct.PieceTable.html
+    pub fn open_file(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let source = FileSource::open(path)?;
+        self.set_source(source);
+        Ok(())
+    }
+
+    /// Returns the data of an [`Hexview`], with all the edits made to it.
+    ///
+    /// The [`ByteSource`] it was created from is available through
+    /// [`PieceTable::original`].
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`ByteSource`]: trait.ByteSource.html
+    /// [`PieceTable::original`]: struct.PieceTable.html#method.original
+    pub fn data(&self) -> &PieceTable {
+        &self.data
+    }
+
+    /// Overwrites the data starting at `offset` with `bytes`.
+    ///
+    /// Like every other change to the data, this can be undone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` goes past the end of the data.
+    pub fn write(&mut self, offset: usize, bytes: &[u8]) -> Edit {
+        let end = offset + bytes.len();
+        assert!(end <= self.data.len(), "write out of bounds");
+
+        self.apply(offset..end, bytes, false)
+    }
+
+    /// Overwrites every byte in `range` with `byte`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` goes past the end of the data.
+    pub fn fill(&mut self, range: Range<usize>, byte: u8) -> Edit {
+        let bytes = vec![byte; range.end.saturating_sub(range.start)];
+
+        self.write(range.start, &bytes)
+    }
+
+    /// Inserts `bytes` before `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the data.
+    pub fn insert(&mut self, offset: usize, bytes: &[u8]) -> Edit {
+        assert!(offset <= self.data.len(), "insert out of bounds");
+
+        self.apply(offset..offset, bytes, false)
+    }
+
+    /// Deletes the bytes in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` goes past the end of the data.
+    pub fn delete(&mut self, range: Range<usize>) -> Edit {
+        assert!(range.start <= range.end && range.end <= self.data.len(), "delete out of bounds");
+
+        self.apply(range, &[], false)
+    }
+
+    /// Reverts the last group of changes made to the data, returning the
+    /// edits that did so.
+    ///
+    /// Consecutive bytes typed by the user are grouped together.
+    pub fn undo(&mut self) -> Vec<Edit> {
+        let group = match self.history.undo() {
+            Some(group) => group.to_vec(),
+            None => return Vec::new(),
+        };
+
+        let edits: Vec<Edit> = group.into_iter().rev().map(|edit| {
+            self.data.replace(edit.offset..(edit.offset + edit.new.len()), &edit.old);
+
+            Edit {
+                offset: edit.offset,
+                old: edit.new,
+                new: edit.old,
+            }
+        }).collect();
+
+        self.move_to_edits(&edits);
+        edits
+    }
+
+    /// Makes the last group of changes reverted by [`undo`] again, returning
+    /// the edits that did so.
+    ///
+    /// [`undo`]: #method.undo
+    pub fn redo(&mut self) -> Vec<Edit> {
+        let group = match self.history.redo() {
+            Some(group) => group.to_vec(),
+            None => return Vec::new(),
+        };
+
+        for edit in &group {
+            self.data.replace(edit.offset..(edit.offset + edit.old.len()), &edit.new);
+        }
+
+        self.move_to_edits(&group);
+        group
+    }
+
+    /// Returns whether there are changes that can be reverted by [`undo`].
+    ///
+    /// [`undo`]: #method.undo
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// Returns whether there are changes that can be made again by [`redo`].
+    ///
+    /// [`redo`]: #method.redo
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Sets how many groups of changes can be undone.
+    ///
+    /// The default is 1000.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+    }
+
+    /// Forgets every change that could be undone or redone.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Returns whether typing in an [`Hexview`] inserts bytes instead of
+    /// overwriting them.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn insert_mode(&self) -> bool {
+        self.insert_mode
+    }
+
+    /// Sets whether typing in an [`Hexview`] inserts bytes instead of
+    /// overwriting them.
+    ///
+    /// The user can toggle it with the Insert key.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert_mode = insert_mode;
+
+        if !insert_mode && self.cursor >= self.data.len() {
+            self.cursor = self.data.len().saturating_sub(1);
+        }
+    }
+
+    /// Replaces the bytes in `range` with `bytes`, recording it in the history.
+    fn apply(&mut self, range: Range<usize>, bytes: &[u8], merge: bool) -> Edit {
+        let edit = self.replace_data(range, bytes);
+
+        self.history.record(edit.clone(), merge);
+        edit
+    }
+
+    /// Replaces the bytes in `range` with `bytes`, without recording it.
+    fn replace_data(&mut self, range: Range<usize>, bytes: &[u8]) -> Edit {
+        let offset = range.start;
+
+        self.mark_edited(range.clone(), bytes.len());
+        let old = self.data.replace(range, bytes);
+        let edit = Edit {
+            offset,
+            old,
+            new: bytes.to_vec(),
+        };
+
+        if edit.old.len() != edit.new.len() {
+            self.selection = None;
+        }
+
+        // Matches can't be trusted after the data changes
+        self.matches.clear();
+        self.revision += 1;
+
+        self.clamp_cursor();
+        edit
+    }
+
+    /// Notes that the bytes in `range` were replaced with `len` bytes, so a
+    /// [`Diff`] only compares what changed.
+    ///
+    /// [`Diff`]: diff/struct.Diff.html
+    fn mark_edited(&mut self, range: Range<usize>, len: usize) {
+        // Everything after an edit that changes the length moves
+        let end = if range.end - range.start == len { range.end } else { usize::MAX };
+
+        self.edited = Some(match self.edited.take() {
+            Some(edited) => edited.start.min(range.start)..edited.end.max(end),
+            None => range.start..end,
+        });
+    }
+
+    /// Moves the cursor to where the last of `edits` happened.
+    fn move_to_edits(&mut self, edits: &[Edit]) {
+        for edit in edits {
+            self.mark_edited(edit.offset..(edit.offset + edit.old.len()), edit.new.len());
+        }
+
+        if let Some(edit) = edits.last() {
+            self.cursor = edit.offset;
+        }
+
+        self.selection = None;
+        self.matches.clear();
+        self.digit = 0;
+        self.revision += 1;
+        self.clamp_cursor();
+    }
+
+    /// Scrolls just enough for the row of the cursor to be visible in the
+    /// last [`Hexview`] the state was shown in.
+    ///
+    /// Before it's shown in any, the scrolling is left pending for the
+    /// first [`Hexview`] to do.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    fn scroll_to_cursor(&mut self) {
+        match self.view {
+            Some(view) => self.scroll = self.cursor_scroll(view),
+            None => self.cursor_scroll_pending = true,
+        }
+    }
+
+    /// The scroll position closest to the current one where the row of the
+    /// cursor is visible in `view`.
+    fn cursor_scroll(&self, view: View) -> ScrollPosition {
+        // Everything is in rows, which stay precise with huge data
+        let visible_rows = view.visible_height as f64 / view.row_height as f64;
+        let row = (self.cursor / view.column_count) as f64;
+        let mut top = self.scroll.rows(view.row_height);
+
+        if row < top {
+            top = row;
+        } else if row + 1.0 > top + visible_rows {
+            top = row + 1.0 - visible_rows;
+        }
+
+        ScrollPosition::from_rows(clamp(top, 0.0, self.max_scroll_rows(view)), view.row_height)
+    }
+
+    /// The amount of rows that can be scrolled past in `view`, with a
+    /// fraction.
+    fn max_scroll_rows(&self, view: View) -> f64 {
+        let visible_rows = view.visible_height as f64 / view.row_height as f64;
+
+        // The cursor may be right past the end when inserting
+        let len = self.data.len().max(self.cursor + 1);
+        let row_count = (len + view.column_count - 1) / view.column_count;
+
+        (row_count as f64 - visible_rows).max(0.0)
+    }
+
+    /// Clamps the scroll position to what fits in the last [`Hexview`] the
+    /// state was shown in.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    fn clamp_scroll(&mut self) {
+        if let Some(view) = self.view {
+            let max_rows = self.max_scroll_rows(view);
+
+            if self.scroll.rows(view.row_height) > max_rows {
+                self.scroll = ScrollPosition::from_rows(max_rows, view.row_height);
+            }
+        }
+    }
+
+    fn clamp_cursor(&mut self) {
+        let len = self.data.len();
+        let last = if self.insert_mode { len } else { len.saturating_sub(1) };
+
+        self.cursor = self.cursor.min(last);
+    }
+
+    /// Moves the cursor of an [`Hexview`] to `offset`, scrolling it into view.
+    ///
+    /// The offset is clamped to the data, and the selection is cleared.
+    /// [`parse_offset`] can be used to get it from what the user typed.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`parse_offset`]: fn.parse_offset.html
+    pub fn goto(&mut self, offset: usize) {
+        self.cursor = offset;
+        self.selection = None;
+        self.digit = 0;
+        self.history.close_group();
+        self.clamp_cursor();
+        self.scroll_to_cursor();
+    }
+
+    /// Searches the data of an [`Hexview`] for `pattern`, returning every
+    /// match.
+    ///
+    /// The matches are highlighted until the next search, until they are
+    /// cleared, or until the data is edited. The user can step between them
+    /// with F3 and Shift+F3.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn search(&mut self, pattern: &Pattern) -> &[Range<usize>] {
+        self.matches = find_all(&self.data, pattern);
+        &self.matches
+    }
+
+    /// Searches the data of an [`Hexview`] for a [`ByteRegex`], returning
+    /// every match with its capture groups.
+    ///
+    /// The matches are highlighted just like the ones of [`search`].
+    ///
+    /// This blocks until the whole data is searched or `cancel` is set, which
+    /// leaves the matches as they were. Use [`find_regex`] on another thread
+    /// with [`set_matches`] for big files instead.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    /// [`ByteRegex`]: struct.ByteRegex.html
+    /// [`search`]: #method.search
+    /// [`find_regex`]: fn.find_regex.html
+    /// [`set_matches`]: #method.set_matches
+    pub fn search_regex(
+        &mut self,
+        regex: &ByteRegex,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<Vec<RegexMatch>, Cancelled> {
+        let found = find_regex(&self.data, regex, DEFAULT_MAX_MATCH_LEN, cancel)?;
+
+        self.matches = found.iter().map(|m| m.range.clone()).collect();
+        Ok(found)
+    }
+
+    /// Returns the matches of the last search.
+    pub fn matches(&self) -> &[Range<usize>] {
+        &self.matches
+    }
+
+    /// Sets the matches to highlight, as if they were found by a search.
+    pub fn set_matches(&mut self, mut matches: Vec<Range<usize>>) {
+        matches.sort_by_key(|range| range.start);
+        self.matches = matches;
+    }
+
+    /// Forgets the matches of the last search.
+    pub fn clear_matches(&mut self) {
+        self.matches.clear();
+    }
+
+    /// Selects the first match at the cursor or after it, wrapping around to
+    /// the start of the data.
+    ///
+    /// If a match is already selected, the one after it is selected instead.
+    pub fn next_match(&mut self) -> Option<Range<usize>> {
+        let (from, is_selected) = self.match_reference();
+        let index = self.matches.iter().position(|range| {
+            range.start > from || (range.start == from && !is_selected)
+        });
+
+        self.select_match(index.or(if self.matches.is_empty() { None } else { Some(0) }))
+    }
+
+    /// Selects the last match before the cursor, wrapping around to the end
+    /// of the data.
+    pub fn previous_match(&mut self) -> Option<Range<usize>> {
+        let (from, _) = self.match_reference();
+        let index = self.matches.iter().rposition(|range| range.start < from);
+
+        self.select_match(index.or(self.matches.len().checked_sub(1)))
+    }
+
+    /// Where matches are looked for from, which is the start of the
+    /// selection if there's one, and whether it's a match.
+    fn match_reference(&self) -> (usize, bool) {
+        match self.selection {
+            Some(selection) => {
+                let range = selection.range();
+                let found = self.matches.binary_search_by_key(&range.start, |found| found.start);
+                let is_match = matches!(found, Ok(index) if self.matches[index] == range);
+
+                (range.start, is_match)
+            }
+            None => (self.cursor, false),
+        }
+    }
+
+    fn select_match(&mut self, index: Option<usize>) -> Option<Range<usize>> {
+        let range = self.matches[index?].clone();
+
+        self.set_selection(Some(Selection::new(range.start, range.end)));
+        self.scroll_to_cursor();
+
+        Some(range)
+    }
+
+    /// Replaces the next match of `pattern` with `replacement`, selecting
+    /// the replaced bytes.
+    ///
+    /// The selected match is replaced if there's one, like after stepping to
+    /// it with [`next_match`]. Otherwise, the first match at the cursor or
+    /// after it is, wrapping around to the start of the data. Returns where
+    /// the replacement is, if a match was found.
+    ///
+    /// [`next_match`]: #method.next_match
+    pub fn replace_next(
+        &mut self,
+        pattern: &Pattern,
+        replacement: &[u8],
+        mode: ReplaceMode,
+    ) -> Result<Option<Range<usize>>, ReplaceError> {
+        mode.check(pattern, replacement)?;
+
+        let selected = self.selection
+            .map(|selection| selection.range())
+            .filter(|range| self.matches.contains(range) && {
+                let mut bytes = vec![0; range.len()];
+                self.data.read(range.start, &mut bytes);
+                pattern.is_match(&bytes)
+            });
+        let found = selected
+            .or_else(|| find_next(&self.data, pattern, self.cursor))
+            .or_else(|| find_next(&self.data, pattern, 0));
+
+        Ok(found.map(|range| {
+            let edit = self.apply(range, replacement, false);
+            let replaced = edit.offset..(edit.offset + replacement.len());
+
+            self.set_selection(Some(Selection::new(replaced.start, replaced.end)));
+            self.scroll_to_cursor();
+            replaced
+        }))
+    }
+
+    /// Replaces every match of `pattern` with `replacement`, returning where
+    /// the replacements are.
+    ///
+    /// The replacements are highlighted like the matches of a [`search`], and
+    /// they are undone all at once.
+    ///
+    /// [`search`]: #method.search
+    pub fn replace_all(
+        &mut self,
+        pattern: &Pattern,
+        replacement: &[u8],
+        mode: ReplaceMode,
+    ) -> Result<Vec<Range<usize>>, ReplaceError> {
+        mode.check(pattern, replacement)?;
+
+        let found = find_all(&self.data, pattern);
+
+        if found.is_empty() {
+            self.matches.clear();
+            return Ok(Vec::new());
+        }
+
+        let removed = self.data.replace_ranges(&found, replacement);
+
+        for range in &found {
+            self.mark_edited(range.clone(), replacement.len());
+        }
+
+        // Recorded as if replaced from the end, which keeps the offsets of the
+        // other matches
+        let edits = found
+            .iter()
+            .zip(removed)
+            .rev()
+            .map(|(range, old)| Edit {
+                offset: range.start,
+                old,
+                new: replacement.to_vec(),
+            })
+            .collect();
+
+        self.history.record_group(edits);
+
+        if pattern.len() != replacement.len() {
+            self.selection = None;
+        }
+
+        self.revision += 1;
+        self.clamp_cursor();
+
+        let delta = replacement.len() as isize - pattern.len() as isize;
+        let replaced: Vec<Range<usize>> = found
+            .iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let start = (range.start as isize + delta * i as isize) as usize;
+                start..(start + replacement.len())
+            })
+            .collect();
+
+        self.matches = replaced.clone();
+        Ok(replaced)
+    }
+
+    /// Marks the bytes in `range` with a background `color`, showing `label`
+    /// when the mouse is over them.
+    ///
+    /// Regions can overlap, the ones added later are drawn on top.
+    pub fn add_region(
+        &mut self,
+        range: Range<usize>,
+        color: Color,
+        label: impl Into<String>,
+    ) -> RegionId {
+        let id = RegionId(self.next_region_id);
+
+        self.next_region_id += 1;
+        self.regions.push(Region {
+            range,
+            color,
+            label: label.into(),
+        });
+        self.region_ids.push(id);
+
+        id
+    }
+
+    /// Removes the [`Region`] identified by `id`, returning it.
+    ///
+    /// [`Region`]: struct.Region.html
+    pub fn remove_region(&mut self, id: RegionId) -> Option<Region> {
+        let index = self.region_ids.iter().position(|region_id| *region_id == id)?;
+
+        self.region_ids.remove(index);
+        Some(self.regions.remove(index))
+    }
+
+    /// Returns the [`Region`] identified by `id`.
+    ///
+    /// [`Region`]: struct.Region.html
+    pub fn region(&self, id: RegionId) -> Option<&Region> {
+        let index = self.region_ids.iter().position(|region_id| *region_id == id)?;
+
+        self.regions.get(index)
+    }
+
+    /// Removes every [`Region`].
+    ///
+    /// [`Region`]: struct.Region.html
+    pub fn clear_regions(&mut self) {
+        self.regions.clear();
+        self.region_ids.clear();
+    }
+
+    /// Reads the fields of `template` starting at `offset`, coloring them
+    /// and labeling them with their values.
+    ///
+    /// The fields aren't read again when the data is edited, apply the
+    /// [`Template`] again to update them.
+    ///
+    /// [`Template`]: struct.Template.html
+    pub fn apply_template(
+        &mut self,
+        template: &Template,
+        offset: usize,
+    ) -> Result<&Field, TemplateError> {
+        let root = template.apply(&self.data, offset)?;
+
+        self.template_leaves = root
+            .leaves()
+            .into_iter()
+            .map(|field| field.range.clone())
+            .filter(|range| !range.is_empty())
+            .collect();
+
+        self.template = Some(root);
+
+        Ok(self.template.as_ref().unwrap())
+    }
+
+    /// Returns the root of the fields read by the last applied [`Template`].
+    ///
+    /// [`Template`]: struct.Template.html
+    pub fn template_fields(&self) -> Option<&Field> {
+        self.template.as_ref()
+    }
+
+    /// Returns the innermost template [`Field`] at the cursor.
+    ///
+    /// [`Field`]: struct.Field.html
+    pub fn field_at_cursor(&self) -> Option<&Field> {
+        self.template.as_ref()?.field_at(self.cursor)
+    }
+
+    /// Removes the fields read by the last applied [`Template`].
+    ///
+    /// [`Template`]: struct.Template.html
+    pub fn clear_template(&mut self) {
+        self.template = None;
+        self.template_leaves.clear();
+    }
+
+    /// Returns the input of the go-to overlay of an [`Hexview`], if it's open.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn goto_input(&self) -> Option<&GotoInput> {
+        self.goto_input.as_ref()
+    }
+
+    /// Returns the offset of the cursor of an [`Hexview`].
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Returns the selection of an [`Hexview`], if there's one.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    /// Sets the selection of an [`Hexview`], moving the cursor to its active
+    /// end.
+    ///
+    /// Both ends are clamped to the length of the data, and an empty
+    /// selection clears it.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn set_selection(&mut self, selection: Option<Selection>) {
+        let len = self.data.len();
+        let selection = selection.map(|selection| Selection::new(
+            selection.anchor.min(len),
+            selection.active.min(len),
+        ));
+
+        if let Some(selection) = selection {
+            self.cursor = selection.active;
+            self.digit = 0;
+            self.history.close_group();
+            self.clamp_cursor();
+        }
+
+        self.selection = selection.filter(|selection| !selection.is_empty());
+    }
+
+    /// Selects all the data in an [`Hexview`].
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn select_all(&mut self) {
+        self.set_selection(Some(Selection::new(0, self.data.len())));
+    }
+
+    /// Returns the pane of an [`Hexview`] that gets what's typed.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn pane(&self) -> Pane {
+        self.pane
+    }
+
+    /// Sets the pane of an [`Hexview`] that gets what's typed.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn set_pane(&mut self, pane: Pane) {
+        self.pane = pane;
+        self.digit = 0;
+        self.history.close_group();
+    }
+
+    /// Returns how far the rows of an [`Hexview`] are scrolled.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn scroll_position(&self) -> ScrollPosition {
+        self.scroll
+    }
+
+    /// Sets the keyboard focus of an [`Hexview`].
+    ///
+    /// The keyboard focus is automatically determined by whether the user has
+    /// cicked inside the widget, but can be manually set in order to use
+    /// shortcuts and move around.
+    ///
+    /// [`Hexview`]: struct.Heview.html
+    pub fn set_keyboard_focus(&mut self, focus: bool) {
+        self.keyboard_focus = focus;
+    }
+}
+
+impl Scrollbar {
+    /// Calculates the rows scrolled past for the scroller top at
+    /// `scroller_y`.
+    fn scroll_rows(&self, scroller_y: f32, max_rows: f64) -> f64 {
+        let track_height = self.bounds.height - self.scroller.height;
+
+        if track_height <= 0.0 {
+            return 0.0;
+        }
+
+        clamp((scroller_y - self.bounds.y) / track_height, 0.0, 1.0) as f64 * max_rows
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Hexview<'a, Message, Renderer>
+where
+    Renderer: self::Renderer
+{
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        self.height
+    }
+
+    fn layout(
+        &self,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let limits = limits.width(Length::Fill).height(self.height);
+        let max_width = limits.max().width;
+
+        self.fit_columns(renderer, max_width);
+
+        let rows_size = self.row_height() * self.row_count() as f32;
+
+        // Vertical margins + top headers + rows
+        let height = MARGINS.y * 2.0 + self.row_height() + rows_size;
+
+        layout::Node::new(limits.resolve(Size::new(max_width, height)))
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        renderer: &Renderer,
+        _clipboard: Option<&dyn Clipboard>,
+    ) {
+        let cursor = self.state.cursor;
+        let selection = self.state.selection;
+        let keyboard_focus = self.state.keyboard_focus;
+        let field = self.state.field_at_cursor().map(|field| field.range.clone());
+
+        self.handle_event(event, layout, cursor_position, messages, renderer);
+
+        if self.state.cursor != cursor {
+            if let Some(on_cursor_move) = &self.on_cursor_move {
+                messages.push(on_cursor_move(self.state.cursor));
+            }
+        }
+
+        if self.state.selection != selection {
+            if let Some(on_selection) = &self.on_selection {
+                messages.push(on_selection(self.state.selection.map(|selection| selection.range())));
+            }
+        }
+
+        if self.state.keyboard_focus != keyboard_focus {
+            if let Some(on_focus) = &self.on_focus {
+                messages.push(on_focus(self.state.keyboard_focus));
+            }
+        }
+
+        if let Some(on_field) = &self.on_field {
+            let current = self.state.field_at_cursor();
+
+            if current.map(|field| field.range.clone()) != field {
+                messages.push(on_field(current.map(|field| field.path.clone())));
+            }
+        }
+    }
+
+    fn draw(
         &self,
         renderer: &mut Renderer,
         _defaults: &Renderer::Defaults,
         layout: Layout<'_>,
         cursor_position: Point,
-        _viewport: &Rectangle,
+        viewport: &Rectangle,
     ) -> Renderer::Output {
+        self.fit_columns(renderer, layout.bounds().width);
+
         renderer.draw(
             layout.bounds(),
             cursor_position,
+            viewport,
             &self.style,
             self.font_size,
-            self.column_count as usize,
+            self.row_len(),
             self.state.keyboard_focus,
             self.state.cursor,
             self.state.test_offset,
             self.state.debug_enabled,
             self.header_font,
             self.data_font,
-            &self.state.selection,
-            &self.state.bytes,
+            self.state.selection.map(|selection| selection.range()),
+            self.display_radix,
+            self.group_size as usize,
+            self.endianness,
+            self.offset_format,
+            self.base_address,
+            if self.is_editable() { Some(self.state.digit) } else { None },
+            self.encoding.as_ref(),
+            self.state.pane,
+            self.state.insert_mode && self.is_editable(),
+            self.scroll_position(layout.bounds()),
+            self.scrollbar(layout.bounds()),
+            self.state.goto_input.as_ref(),
+            &self.state.differences,
+            &self.state.matches,
+            &self.state.regions,
+            &self.state.template_leaves,
+            self.hover_label(renderer, layout.bounds(), cursor_position).as_deref(),
+            &self.state.data,
         )
     }
 
@@ -410,8 +2495,65 @@ where
         struct Marker;
 
         std::any::TypeId::of::<Marker>().hash(state);
-        self.state.bytes_hash.hash(state);
+
+        // The amount of columns may not be fitted yet, so everything it and
+        // the amount of rows depend on is hashed instead
+        self.columns.hash(state);
+        self.state.data.len().max(self.state.cursor + 1).hash(state);
+        self.font_size.to_bits().hash(state);
+        self.display_radix.hash(state);
+        self.group_size.hash(state);
+        self.offset_format.hash(state);
+        self.base_address.hash(state);
+        self.height.hash(state);
+    }
+}
+
+/// Finds the run of printable characters around `offset` in `encoding`, like
+/// a word in the text pane.
+fn printable_run(source: &dyn ByteSource, encoding: &dyn Encoding, offset: usize) -> Range<usize> {
+    const CHUNK_SIZE: usize = 256;
+
+    if offset >= source.len() {
+        return offset..offset;
     }
+
+    let mut start = offset;
+
+    while start > 0 {
+        let chunk_start = start.saturating_sub(CHUNK_SIZE);
+        let chunk = encoding::glyphs(encoding, source, chunk_start..start);
+
+        match chunk.iter().rposition(|glyph| !glyph.is_printable()) {
+            Some(i) => {
+                start = chunk_start + i + 1;
+                break;
+            }
+            None => start = chunk_start,
+        }
+    }
+
+    let mut end = offset;
+
+    loop {
+        let chunk = encoding::glyphs(encoding, source, end..(end + CHUNK_SIZE));
+
+        match chunk.iter().position(|glyph| !glyph.is_printable()) {
+            Some(i) => {
+                end += i;
+                break;
+            }
+            None if chunk.is_empty() => break,
+            None => end += chunk.len(),
+        }
+    }
+
+    // A non-printable byte is selected on its own
+    if start == end {
+        end += 1;
+    }
+
+    start..end
 }
 
 impl<'a, Message, Renderer> From<Hexview<'a, Message, Renderer>>
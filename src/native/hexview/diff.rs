@@ -0,0 +1,504 @@
+//! Widget logic for a [`Diff`] of two [`Hexview`]s in native plataforms.
+//!
+//! [`Diff`]: struct.Diff.html
+//! [`Hexview`]: ../struct.Hexview.html
+
+use iced_native::{
+    keyboard, layout,
+    Clipboard, Element, Event, Font, Hasher, Layout, Length,
+    Point, Rectangle, Size, Widget,
+};
+use std::{hash::Hash, ops::Range, rc::Rc, sync::Arc};
+use crate::graphics::hexview::MARGINS;
+use super::{find_differences, update_differences, Edit, Hexview, ScrollPosition};
+
+/// Two [`Hexview`]s side by side, with their differences highlighted.
+///
+/// Both sides scroll together and keep their cursors at the same offset. The
+/// user can step between the differences with F8 and Shift+F8.
+///
+/// [`Hexview`]: ../struct.Hexview.html
+#[allow(missing_debug_implementations)]
+pub struct Diff<'a, Message, Renderer: self::Renderer> {
+    left: Hexview<'a, Message, Renderer>,
+    right: Hexview<'a, Message, Renderer>,
+    revisions: &'a mut (u64, u64),
+}
+
+/// The local state of a [`Diff`].
+///
+/// [`Diff`]: struct.Diff.html
+#[derive(Debug)]
+pub struct State {
+    left: super::State,
+    right: super::State,
+    revisions: (u64, u64),
+}
+
+/// One of the sides of a [`Diff`].
+///
+/// [`Diff`]: struct.Diff.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// The side on the left.
+    Left,
+    /// The side on the right.
+    Right,
+}
+
+/// The renderer of a [`Diff`].
+///
+/// [`Diff`]: struct.Diff.html
+pub trait Renderer: super::Renderer {
+    /// Combines what was drawn for both sides of a `Diff`.
+    fn draw_diff(&mut self, left: Self::Output, right: Self::Output) -> Self::Output;
+}
+
+impl<'a, Message, Renderer: self::Renderer> Diff<'a, Message, Renderer> {
+    /// Creates a new [`Diff`] with the given [`State`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    /// [`State`]: struct.State.html
+    pub fn new(state: &'a mut State) -> Self {
+        state.refresh();
+
+        let State { left, right, revisions } = state;
+
+        Self {
+            left: Hexview::new(left),
+            right: Hexview::new(right),
+            revisions,
+        }
+    }
+
+    /// Sets the style of both sides of a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn style<S>(mut self, style: S) -> Self
+    where
+        S: Clone + Into<Renderer::Style>,
+    {
+        self.left = self.left.style(style.clone());
+        self.right = self.right.style(style);
+        self
+    }
+
+    /// Sets the size of the fonts in a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.left = self.left.font_size(size);
+        self.right = self.right.font_size(size);
+        self
+    }
+
+    /// Sets the font for column headers and offsets in a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn header_font(mut self, font: Font) -> Self {
+        self.left = self.left.header_font(font);
+        self.right = self.right.header_font(font);
+        self
+    }
+
+    /// Sets the font for bytes and text in a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn data_font(mut self, font: Font) -> Self {
+        self.left = self.left.data_font(font);
+        self.right = self.right.data_font(font);
+        self
+    }
+
+    /// Sets the amount of columns of both sides of a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn column_count(mut self, count: u8) -> Self {
+        self.left = self.left.column_count(count);
+        self.right = self.right.column_count(count);
+        self
+    }
+
+    /// Sets the [`Radix`] of both sides of a [`Diff`].
+    ///
+    /// [`Radix`]: ../enum.Radix.html
+    /// [`Diff`]: struct.Diff.html
+    pub fn display_radix(mut self, radix: super::Radix) -> Self {
+        self.left = self.left.display_radix(radix);
+        self.right = self.right.display_radix(radix);
+        self
+    }
+
+    /// Sets how many bytes are shown together on both sides of a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn group_size(mut self, size: u8) -> Self {
+        self.left = self.left.group_size(size);
+        self.right = self.right.group_size(size);
+        self
+    }
+
+    /// Sets the [`Endianness`] groups of bytes are shown in, on both sides
+    /// of a [`Diff`].
+    ///
+    /// [`Endianness`]: ../template/enum.Endianness.html
+    /// [`Diff`]: struct.Diff.html
+    pub fn endianness(mut self, endianness: super::Endianness) -> Self {
+        self.left = self.left.endianness(endianness);
+        self.right = self.right.endianness(endianness);
+        self
+    }
+
+    /// Sets whether the cursors and selections of a [`Diff`] move a whole
+    /// group of bytes at a time.
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn snap_to_groups(mut self, snap: bool) -> Self {
+        self.left = self.left.snap_to_groups(snap);
+        self.right = self.right.snap_to_groups(snap);
+        self
+    }
+
+    /// Sets the [`Encoding`] of the text panes of a [`Diff`].
+    ///
+    /// [`Encoding`]: ../encoding/trait.Encoding.html
+    /// [`Diff`]: struct.Diff.html
+    pub fn encoding<E>(mut self, encoding: E) -> Self
+    where
+        E: super::Encoding + Clone + 'a,
+    {
+        self.left = self.left.encoding(encoding.clone());
+        self.right = self.right.encoding(encoding);
+        self
+    }
+
+    /// Sets the addresses of the first byte on each side of a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn base_address(mut self, left: u64, right: u64) -> Self {
+        self.left = self.left.base_address(left);
+        self.right = self.right.base_address(right);
+        self
+    }
+
+    /// Sets the [`OffsetFormat`] of both sides of a [`Diff`].
+    ///
+    /// [`OffsetFormat`]: ../enum.OffsetFormat.html
+    /// [`Diff`]: struct.Diff.html
+    pub fn offset_format(mut self, format: super::OffsetFormat) -> Self {
+        self.left = self.left.offset_format(format);
+        self.right = self.right.offset_format(format);
+        self
+    }
+
+    /// Sets how the amount of [`Columns`] of both sides of a [`Diff`] is
+    /// chosen.
+    ///
+    /// [`Columns`]: ../enum.Columns.html
+    /// [`Diff`]: struct.Diff.html
+    pub fn columns(mut self, columns: super::Columns) -> Self {
+        self.left = self.left.columns(columns);
+        self.right = self.right.columns(columns);
+        self
+    }
+
+    /// Sets the height of a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn height(mut self, height: Length) -> Self {
+        self.left = self.left.height(height);
+        self.right = self.right.height(height);
+        self
+    }
+
+    /// Sets whether the data of both sides of a [`Diff`] can't be edited.
+    /// They're read-only by default.
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.left = self.left.read_only(read_only);
+        self.right = self.right.read_only(read_only);
+        self
+    }
+
+    /// Sets the message that should be produced when the user edits the data
+    /// of either side of a [`Diff`].
+    ///
+    /// [`Diff`]: struct.Diff.html
+    pub fn on_edit<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(Side, Edit) -> Message,
+    {
+        let f = Rc::new(f);
+        let on_right = f.clone();
+
+        self.left = self.left.on_edit(move |edit| f(Side::Left, edit));
+        self.right = self.right.on_edit(move |edit| on_right(Side::Right, edit));
+        self
+    }
+
+    /// Makes the side that didn't change follow the cursor and scrolling of
+    /// the one that did.
+    fn sync(&mut self, cursors: (usize, usize), scrolls: (ScrollPosition, ScrollPosition)) {
+        let (left, right) = (&mut *self.left.state, &mut *self.right.state);
+
+        if left.cursor != cursors.0 {
+            right.cursor = left.cursor;
+            right.clamp_cursor();
+        } else if right.cursor != cursors.1 {
+            left.cursor = right.cursor;
+            left.clamp_cursor();
+        }
+
+        // The shorter side may not scroll as far as the other one
+        if left.scroll != scrolls.0 {
+            right.scroll = left.scroll;
+            right.clamp_scroll();
+        } else if right.scroll != scrolls.1 {
+            left.scroll = right.scroll;
+            left.clamp_scroll();
+        }
+    }
+}
+
+impl State {
+    /// Creates a new [`State`] comparing two empty sides.
+    ///
+    /// [`State`]: struct.State.html
+    pub fn new() -> Self {
+        let mut state = Self {
+            left: super::State::new(),
+            right: super::State::new(),
+            revisions: (0, 0),
+        };
+
+        state.refresh();
+        state
+    }
+
+    /// Returns the state of the left side.
+    pub fn left(&self) -> &super::State {
+        &self.left
+    }
+
+    /// Returns the state of the left side, to change its data.
+    ///
+    /// The differences are found again the next time the [`Diff`] is shown,
+    /// or when [`refresh`] is called.
+    ///
+    /// [`Diff`]: struct.Diff.html
+    /// [`refresh`]: #method.refresh
+    pub fn left_mut(&mut self) -> &mut super::State {
+        &mut self.left
+    }
+
+    /// Returns the state of the right side.
+    pub fn right(&self) -> &super::State {
+        &self.right
+    }
+
+    /// Returns the state of the right side, to change its data.
+    ///
+    /// The differences are found again the next time the [`Diff`] is shown,
+    /// or when [`refresh`] is called.
+    ///
+    /// [`Diff`]: struct.Diff.html
+    /// [`refresh`]: #method.refresh
+    pub fn right_mut(&mut self) -> &mut super::State {
+        &mut self.right
+    }
+
+    /// Compares both sides again, if either of them changed since the last
+    /// time.
+    pub fn refresh(&mut self) {
+        refresh(&mut self.left, &mut self.right, &mut self.revisions);
+    }
+
+    /// Returns the ranges of bytes that differ between both sides, sorted.
+    ///
+    /// When one side is longer, the bytes the other one doesn't have are a
+    /// difference too.
+    pub fn differences(&self) -> &[Range<usize>] {
+        &self.left.differences
+    }
+
+    /// Returns how many bytes differ between both sides.
+    pub fn different_bytes(&self) -> usize {
+        self.differences().iter().map(|range| range.end - range.start).sum()
+    }
+
+    /// Moves the cursors to the next difference after them, wrapping around
+    /// to the first one, and returns it.
+    pub fn next_difference(&mut self) -> Option<Range<usize>> {
+        self.refresh();
+        step(&mut self.left, &mut self.right, true)
+    }
+
+    /// Moves the cursors to the previous difference before them, wrapping
+    /// around to the last one, and returns it.
+    pub fn previous_difference(&mut self) -> Option<Range<usize>> {
+        self.refresh();
+        step(&mut self.left, &mut self.right, false)
+    }
+
+    /// Moves the cursors of both sides to `offset`.
+    pub fn goto(&mut self, offset: usize) {
+        self.left.goto(offset);
+        self.right.goto(offset);
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Diff<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+{
+    fn width(&self) -> Length {
+        Length::Fill
+    }
+
+    fn height(&self) -> Length {
+        self.left.height
+    }
+
+    fn layout(
+        &self,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let limits = limits.width(Length::Fill).height(self.left.height);
+        let max = limits.max();
+        let side_width = ((max.width - MARGINS.x) / 2.0).floor().max(0.0);
+        let side_limits = layout::Limits::new(Size::ZERO, Size::new(side_width, max.height));
+
+        let left = self.left.layout(renderer, &side_limits);
+        let mut right = self.right.layout(renderer, &side_limits);
+        right.move_to(Point::new(side_width + MARGINS.x, 0.0));
+
+        let height = left.size().height.max(right.size().height);
+
+        layout::Node::with_children(Size::new(max.width, height), vec![left, right])
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        renderer: &Renderer,
+        _clipboard: Option<&dyn Clipboard>,
+    ) {
+        use keyboard::{Event as KeyboardEvent, KeyCode};
+
+        let mut children = layout.children();
+        let (left_layout, right_layout) = match (children.next(), children.next()) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return,
+        };
+
+        let cursors = (self.left.state.cursor, self.right.state.cursor);
+        let scrolls = (self.left.state.scroll, self.right.state.scroll);
+        let keyboard_focus = self.left.state.keyboard_focus || self.right.state.keyboard_focus;
+
+        if let Event::Keyboard(KeyboardEvent::KeyPressed { key_code: KeyCode::F8, modifiers }) = event {
+            if keyboard_focus {
+                step(self.left.state, self.right.state, !modifiers.shift);
+                return;
+            }
+        }
+
+        self.left.handle_event(event.clone(), left_layout, cursor_position, messages, renderer);
+        self.right.handle_event(event, right_layout, cursor_position, messages, renderer);
+        self.sync(cursors, scrolls);
+        refresh(self.left.state, self.right.state, self.revisions);
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        viewport: &Rectangle,
+    ) -> Renderer::Output {
+        let mut children = layout.children();
+        let left_layout = children.next().expect("a diff lays out its left side");
+        let right_layout = children.next().expect("a diff lays out its right side");
+
+        let left = self.left.draw(renderer, defaults, left_layout, cursor_position, viewport);
+        let right = self.right.draw(renderer, defaults, right_layout, cursor_position, viewport);
+
+        renderer.draw_diff(left, right)
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.left.hash_layout(state);
+        self.right.hash_layout(state);
+    }
+}
+
+/// Compares both sides again if their revisions changed, only around what
+/// was edited since the last time.
+fn refresh(left: &mut super::State, right: &mut super::State, revisions: &mut (u64, u64)) {
+    let current = (left.revision, right.revision);
+
+    // A new state has nothing to compare yet, which is cheap to redo
+    if current == *revisions && current != (0, 0) {
+        return;
+    }
+
+    let edited = match (left.edited.take(), right.edited.take()) {
+        (Some(a), Some(b)) => Some(a.start.min(b.start)..a.end.max(b.end)),
+        (edited, None) | (None, edited) => edited,
+    };
+
+    let differences = match edited {
+        Some(edited) if *revisions != (0, 0) => {
+            update_differences(&left.differences, &left.data, &right.data, edited)
+        }
+        _ => find_differences(&left.data, &right.data),
+    };
+
+    // Both sides share the same differences
+    let differences = Arc::new(differences);
+
+    right.differences = differences.clone();
+    left.differences = differences;
+    *revisions = current;
+}
+
+/// Moves both cursors to the next or previous difference, wrapping around.
+fn step(left: &mut super::State, right: &mut super::State, forward: bool) -> Option<Range<usize>> {
+    let cursor = left.cursor;
+    let differences = &left.differences;
+    let range = if forward {
+        differences.iter().find(|range| range.start > cursor).or_else(|| differences.first())
+    } else {
+        differences.iter().rev().find(|range| range.start < cursor).or_else(|| differences.last())
+    }?
+    .clone();
+
+    left.goto(range.start);
+    right.goto(range.start);
+    Some(range)
+}
+
+impl<'a, Message, Renderer> From<Diff<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + self::Renderer,
+    Message: 'a,
+{
+    fn from(diff: Diff<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(diff)
+    }
+}
@@ -0,0 +1,440 @@
+//! Widget logic for an [`Inspector`] in native plataforms.
+//!
+//! [`Inspector`]: struct.Inspector.html
+
+use iced_native::{
+    keyboard, layout, mouse,
+    Clipboard, Element, Event, Font, Hasher, Layout, Length,
+    Point, Rectangle, Size, Widget,
+};
+use std::{hash::Hash, marker::PhantomData};
+use crate::{
+    graphics::hexview::{LINE_SPACING, MARGINS},
+    native::hexview::{self, ByteSource},
+};
+
+pub use crate::core::hexview::{
+    inspector::{Kind, ValueError, MAX_SIZE},
+    Endianness,
+};
+
+/// How much of the width of an [`Inspector`] the column of names takes.
+///
+/// [`Inspector`]: struct.Inspector.html
+const NAME_WIDTH: f32 = 0.2;
+
+/// A table of the values the bytes at the cursor of an [`Hexview`] can be
+/// read as, in both little and big endian.
+///
+/// Clicking a value lets the user type a new one, which is written back
+/// through the message set with [`on_write`]. Call [`State::inspect`] to
+/// keep it in sync with the [`Hexview`].
+///
+/// [`Hexview`]: ../hexview/struct.Hexview.html
+/// [`on_write`]: #method.on_write
+/// [`State::inspect`]: struct.State.html#method.inspect
+#[allow(missing_debug_implementations)]
+pub struct Inspector<'a, Message, Renderer: self::Renderer> {
+    state: &'a mut State,
+    style: Renderer::Style,
+    header_font: Font,
+    data_font: Font,
+    font_size: f32,
+    width: Length,
+    read_only: bool,
+    on_write: Option<Box<dyn Fn(usize, Vec<u8>) -> Message + 'a>>,
+    message: PhantomData<Message>,
+}
+
+/// The local state of an [`Inspector`].
+///
+/// [`Inspector`]: struct.Inspector.html
+#[derive(Debug, Default)]
+pub struct State {
+    offset: usize,
+    bytes: Vec<u8>,
+    input: Option<Input>,
+}
+
+/// A value being typed in an [`Inspector`].
+///
+/// [`Inspector`]: struct.Inspector.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    /// The kind of value being typed.
+    pub kind: Kind,
+    /// The endianness it will be written in.
+    pub endianness: Endianness,
+    /// What was typed so far.
+    pub value: String,
+    /// Whether the value couldn't be written.
+    pub is_invalid: bool,
+}
+
+/// A column of an [`Inspector`].
+///
+/// [`Inspector`]: struct.Inspector.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    /// The names of the kinds of values.
+    Name,
+    /// The values in little endian.
+    Little,
+    /// The values in big endian.
+    Big,
+}
+
+/// The renderer of an [`Inspector`].
+///
+/// [`Inspector`]: struct.Inspector.html
+pub trait Renderer: iced_native::Renderer {
+    /// The style supported by this renderer.
+    type Style: Default;
+
+    /// Draws an `Inspector`.
+    ///
+    /// `values` has the values of every [`Kind`] in [`Kind::ALL`], in little
+    /// and big endian, or `None` where the bytes don't make one.
+    ///
+    /// [`Kind`]: enum.Kind.html
+    /// [`Kind::ALL`]: enum.Kind.html#associatedconstant.ALL
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        style: &Self::Style,
+        text_size: f32,
+        header_font: Font,
+        data_font: Font,
+        values: &[(Option<String>, Option<String>)],
+        input: Option<&Input>,
+    ) -> Self::Output;
+}
+
+impl<'a, Message, Renderer: self::Renderer> Inspector<'a, Message, Renderer> {
+    /// Creates a new [`Inspector`] with the given [`State`].
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    /// [`State`]: struct.State.html
+    pub fn new(state: &'a mut State) -> Self {
+        Self {
+            state,
+            style: Renderer::Style::default(),
+            header_font: Font::Default,
+            data_font: Font::Default,
+            font_size: 17.0,
+            width: Length::Fill,
+            read_only: false,
+            on_write: None,
+            message: PhantomData,
+        }
+    }
+
+    /// Sets the style of an [`Inspector`].
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn style(mut self, style: impl Into<Renderer::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Sets the size of the fonts in an [`Inspector`].
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Sets the font for the column header and names in an [`Inspector`].
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn header_font(mut self, font: Font) -> Self {
+        self.header_font = font;
+        self
+    }
+
+    /// Sets the font for the values in an [`Inspector`].
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn data_font(mut self, font: Font) -> Self {
+        self.data_font = font;
+        self
+    }
+
+    /// Sets the width of an [`Inspector`].
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets whether the values of an [`Inspector`] can't be edited.
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Sets the message that should be produced when the user types a new
+    /// value, with the offset and the bytes to write there.
+    ///
+    /// Values can't be edited without it.
+    ///
+    /// [`Inspector`]: struct.Inspector.html
+    pub fn on_write<F>(mut self, f: F) -> Self
+    where
+        F: 'a + Fn(usize, Vec<u8>) -> Message,
+    {
+        self.on_write = Some(Box::new(f));
+        self
+    }
+
+    fn is_editable(&self) -> bool {
+        !self.read_only && self.on_write.is_some()
+    }
+
+    fn row_height(&self) -> f32 {
+        self.font_size + LINE_SPACING
+    }
+
+    /// Finds the value under the mouse.
+    fn value_at(&self, bounds: Rectangle, position: Point) -> Option<(Kind, Endianness)> {
+        let row_height = self.row_height();
+
+        Kind::ALL.iter().enumerate().find_map(|(row, kind)| {
+            [(Column::Little, Endianness::Little), (Column::Big, Endianness::Big)]
+                .iter()
+                .find(|(column, _)| {
+                    cell_bounds(bounds, row_height, row + 1, *column).contains(position)
+                })
+                .map(|(_, endianness)| (*kind, *endianness))
+        })
+    }
+
+    fn submit(&mut self, messages: &mut Vec<Message>) {
+        let input = match &mut self.state.input {
+            Some(input) => input,
+            None => return,
+        };
+        // Values are only written over bytes that are already there
+        match input.kind.encode_over(&input.value, input.endianness, &self.state.bytes) {
+            Ok(bytes) => {
+                if let Some(on_write) = &self.on_write {
+                    messages.push(on_write(self.state.offset, bytes));
+                }
+
+                self.state.input = None;
+            }
+            _ => input.is_invalid = true,
+        }
+    }
+}
+
+impl State {
+    /// Creates a new [`State`], with nothing to inspect.
+    ///
+    /// [`State`]: struct.State.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the bytes at the cursor of an [`Hexview`].
+    ///
+    /// [`Hexview`]: ../hexview/struct.Hexview.html
+    pub fn inspect(&mut self, hexview: &hexview::State) {
+        self.inspect_at(hexview.data(), hexview.cursor());
+    }
+
+    /// Reads the bytes of `source` at `offset`.
+    ///
+    /// Whatever was being typed is discarded if the offset changes.
+    pub fn inspect_at(&mut self, source: &dyn ByteSource, offset: usize) {
+        let mut bytes = vec![0; MAX_SIZE];
+        let count = source.read(offset, &mut bytes);
+        bytes.truncate(count);
+
+        if offset != self.offset {
+            self.input = None;
+        }
+
+        self.offset = offset;
+        self.bytes = bytes;
+    }
+
+    /// Returns the offset being inspected.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the value being typed, if there's one.
+    pub fn input(&self) -> Option<&Input> {
+        self.input.as_ref()
+    }
+
+    /// Returns the value of `kind` at the offset, if the bytes make one.
+    pub fn value(&self, kind: Kind, endianness: Endianness) -> Option<String> {
+        kind.decode(&self.bytes, endianness)
+    }
+}
+
+/// Calculates the bounds of a cell of an [`Inspector`], where row `0` is the
+/// column header and the rest follow [`Kind::ALL`].
+///
+/// [`Inspector`]: struct.Inspector.html
+/// [`Kind::ALL`]: enum.Kind.html#associatedconstant.ALL
+pub fn cell_bounds(bounds: Rectangle, row_height: f32, row: usize, column: Column) -> Rectangle {
+    let width = bounds.width - MARGINS.x * 2.0;
+    let name_width = (width * NAME_WIDTH).floor();
+    let value_width = ((width - name_width) / 2.0).floor();
+    let (x, width) = match column {
+        Column::Name => (0.0, name_width),
+        Column::Little => (name_width, value_width),
+        Column::Big => (name_width + value_width, value_width),
+    };
+
+    Rectangle {
+        x: bounds.x + MARGINS.x + x,
+        y: bounds.y + MARGINS.y + row_height * row as f32,
+        width,
+        height: row_height,
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Inspector<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn layout(
+        &self,
+        _renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let limits = limits.width(self.width).height(Length::Shrink);
+        let height = MARGINS.y * 2.0 + self.row_height() * (Kind::ALL.len() + 1) as f32;
+
+        layout::Node::new(limits.resolve(Size::new(limits.max().width, height)))
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        _renderer: &Renderer,
+        _clipboard: Option<&dyn Clipboard>,
+    ) {
+        use keyboard::{Event as KeyboardEvent, KeyCode};
+        use mouse::{Button as MouseButton, Event as MouseEvent};
+
+        let bounds = layout.bounds();
+
+        match event {
+            Event::Mouse(MouseEvent::ButtonPressed(MouseButton::Left)) => {
+                let value = match self.value_at(bounds, cursor_position) {
+                    Some(value) if self.is_editable() => value,
+                    _ => {
+                        self.state.input = None;
+                        return;
+                    }
+                };
+                let (kind, endianness) = value;
+
+                self.state.input = Some(Input {
+                    kind,
+                    endianness,
+                    value: self.state.value(kind, endianness).unwrap_or_default(),
+                    is_invalid: false,
+                });
+            }
+
+            Event::Keyboard(KeyboardEvent::KeyPressed { key_code, .. }) => {
+                let input = match &mut self.state.input {
+                    Some(input) => input,
+                    None => return,
+                };
+
+                match key_code {
+                    KeyCode::Escape => self.state.input = None,
+                    KeyCode::Backspace => {
+                        input.value.pop();
+                        input.is_invalid = false;
+                    }
+                    KeyCode::Enter | KeyCode::NumpadEnter => self.submit(messages),
+                    _ => (),
+                }
+            }
+
+            Event::Keyboard(KeyboardEvent::CharacterReceived(c)) => {
+                if let Some(input) = &mut self.state.input {
+                    if !c.is_control() {
+                        input.value.push(c);
+                        input.is_invalid = false;
+                    }
+                }
+            }
+
+            _ => (),
+        }
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        _defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        _viewport: &Rectangle,
+    ) -> Renderer::Output {
+        let values: Vec<_> = Kind::ALL
+            .iter()
+            .map(|kind| {
+                (
+                    self.state.value(*kind, Endianness::Little),
+                    self.state.value(*kind, Endianness::Big),
+                )
+            })
+            .collect();
+
+        renderer.draw(
+            layout.bounds(),
+            cursor_position,
+            &self.style,
+            self.font_size,
+            self.header_font,
+            self.data_font,
+            &values,
+            self.state.input.as_ref(),
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+
+        std::any::TypeId::of::<Marker>().hash(state);
+        self.width.hash(state);
+        self.font_size.to_bits().hash(state);
+    }
+}
+
+impl<'a, Message, Renderer> From<Inspector<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + self::Renderer,
+    Message: 'a,
+{
+    fn from(inspector: Inspector<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(inspector)
+    }
+}
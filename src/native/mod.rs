@@ -2,6 +2,10 @@
 
 #[cfg(feature = "hexview")]
 pub mod hexview;
+#[cfg(feature = "inspector")]
+pub mod inspector;
 
 #[cfg(feature = "hexview")]
 pub use hexview::Hexview;
+#[cfg(feature = "inspector")]
+pub use inspector::Inspector;
@@ -20,6 +20,18 @@ pub struct Style {
     pub non_printable_color: Option<Color>,
     /// Color for the cursor
     pub cursor_color: Color,
+    /// Color of the scrollbar track
+    pub scrollbar_color: Color,
+    /// Color of the scroller, the draggable part of the scrollbar
+    pub scroller_color: Color,
+    /// Color for input that couldn't be understood
+    pub error_color: Color,
+    /// Background color of search matches
+    pub match_color: Color,
+    /// Background colors alternated between the fields of a template
+    pub field_colors: [Color; 2],
+    /// Background color of bytes that differ from the ones they're compared to
+    pub diff_color: Color,
 }
 
 /// A set of styles for an [`Hexview`]
@@ -52,6 +64,15 @@ impl Light {
         data_color: Color::from_rgb(0.196, 0.196, 0.196),
         non_printable_color: Some(Color::from_rgb(0.64, 0.64, 0.64)),
         cursor_color: Color::from_rgb(0.63, 0.63, 0.63),
+        scrollbar_color: Color::from_rgb(0.94, 0.94, 0.94),
+        scroller_color: Color::from_rgb(0.75, 0.75, 0.75),
+        error_color: Color::from_rgb(0.8, 0.2, 0.2),
+        match_color: Color::from_rgb(1.0, 0.89, 0.55),
+        field_colors: [
+            Color::from_rgb(0.85, 0.92, 1.0),
+            Color::from_rgb(0.88, 0.97, 0.88),
+        ],
+        diff_color: Color::from_rgb(1.0, 0.8, 0.8),
     };
 }
 
@@ -63,6 +84,15 @@ impl Dark {
         data_color: Color::from_rgb(0.44, 0.53, 0.53),
         non_printable_color: Some(Color::from_rgb(0.27, 0.368, 0.368)),
         cursor_color: Color::from_rgb(0.15, 0.38, 0.44),
+        scrollbar_color: Color::from_rgb(0.21, 0.25, 0.26),
+        scroller_color: Color::from_rgb(0.278, 0.33, 0.345),
+        error_color: Color::from_rgb(0.75, 0.3, 0.3),
+        match_color: Color::from_rgb(0.37, 0.33, 0.16),
+        field_colors: [
+            Color::from_rgb(0.17, 0.26, 0.33),
+            Color::from_rgb(0.2, 0.3, 0.22),
+        ],
+        diff_color: Color::from_rgb(0.42, 0.2, 0.2),
     };
 }
 
@@ -0,0 +1,102 @@
+//! Style for an [`Inspector`] widget.
+//!
+//! [`Inspector`]: ../native/inspector/struct.Inspector.html
+use iced_native::Color;
+
+/// The apperance of an [`Inspector`].
+///
+/// [`Inspector`]: ../../native/inspector/struct.Inspector.html
+#[derive(Debug, Clone)]
+pub struct Style {
+    /// Background color
+    pub background_color: Color,
+    /// Color for separators
+    pub line_color: Color,
+    /// Color for the column header
+    pub header_color: Color,
+    /// Color for the names of the kinds of values
+    pub name_color: Color,
+    /// Color for values
+    pub value_color: Color,
+    /// Color for the placeholder shown where the bytes don't make a value
+    pub missing_color: Color,
+    /// Background color of the value being typed
+    pub input_color: Color,
+    /// Color for values that couldn't be written
+    pub error_color: Color,
+}
+
+/// A set of styles for an [`Inspector`]
+///
+/// [`Inspector`]: ../../native/inspector/struct.Inspector.html
+pub trait StyleSheet {
+    /// The active style of an [`Inspector`].
+    ///
+    /// [`Inspector`]: ../../native/inspector/struct.Inspector.html
+    fn active(&self) -> Style;
+}
+
+/// Built-in light theme for [`Inspector`].
+///
+/// [`Inspector`]: ../../native/inspector/struct.Inspector.html
+#[allow(missing_debug_implementations)]
+pub struct Light;
+
+/// Built-in dark theme for [`Inspector`].
+///
+/// [`Inspector`]: ../../native/inspector/struct.Inspector.html
+#[allow(missing_debug_implementations)]
+pub struct Dark;
+
+impl Light {
+    const ACTIVE_STYLE: Style = Style {
+        background_color: Color::from_rgb(1.0, 1.0, 1.0),
+        line_color: Color::from_rgb(0.75, 0.75, 0.75),
+        header_color: Color::from_rgb(0.33, 0.33, 0.33),
+        name_color: Color::from_rgb(0.33, 0.33, 0.33),
+        value_color: Color::from_rgb(0.196, 0.196, 0.196),
+        missing_color: Color::from_rgb(0.64, 0.64, 0.64),
+        input_color: Color::from_rgb(0.94, 0.94, 0.94),
+        error_color: Color::from_rgb(0.8, 0.2, 0.2),
+    };
+}
+
+impl Dark {
+    const ACTIVE_STYLE: Style = Style {
+        background_color: Color::from_rgb(0.18, 0.21, 0.22),
+        line_color: Color::from_rgb(0.278, 0.33, 0.345),
+        header_color: Color::from_rgb(0.294, 0.372, 0.372),
+        name_color: Color::from_rgb(0.294, 0.372, 0.372),
+        value_color: Color::from_rgb(0.44, 0.53, 0.53),
+        missing_color: Color::from_rgb(0.27, 0.368, 0.368),
+        input_color: Color::from_rgb(0.21, 0.25, 0.26),
+        error_color: Color::from_rgb(0.75, 0.3, 0.3),
+    };
+}
+
+impl StyleSheet for Light {
+    fn active(&self) -> Style {
+        Self::ACTIVE_STYLE
+    }
+}
+
+impl StyleSheet for Dark {
+    fn active(&self) -> Style {
+        Self::ACTIVE_STYLE
+    }
+}
+
+impl std::default::Default for Box<dyn StyleSheet> {
+    fn default() -> Self {
+        Box::new(Light)
+    }
+}
+
+impl<T> From<T> for Box<dyn StyleSheet>
+where
+    T: 'static + StyleSheet,
+{
+    fn from(style: T) -> Self {
+        Box::new(style)
+    }
+}
@@ -1,3 +1,4 @@
 //! Style definitions for the widgets.
 
 pub mod hexview;
+pub mod inspector;